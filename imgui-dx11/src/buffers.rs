@@ -0,0 +1,145 @@
+use imgui::{DrawIdx, DrawVert};
+use windows::Win32::Graphics::Direct3D11::*;
+
+use crate::device_and_swapchain::DeviceAndSwapChain;
+
+const INITIAL_VTX_CAPACITY: usize = 5000;
+const INITIAL_IDX_CAPACITY: usize = 10000;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ConstantBuffer {
+    mvp: [[f32; 4]; 4],
+}
+
+/// D3D11 has a single immediate context and no frames-in-flight
+/// bookkeeping, so there's just one dynamic buffer of each kind, remapped
+/// every frame, instead of one set per swap-chain buffer.
+pub struct Buffers {
+    vtx_buffer: ID3D11Buffer,
+    vtx_capacity: usize,
+    idx_buffer: ID3D11Buffer,
+    idx_capacity: usize,
+    mtx_buffer: ID3D11Buffer,
+    filter_uniforms_buffer: Option<ID3D11Buffer>,
+}
+
+impl Buffers {
+    pub fn new(dasc: &DeviceAndSwapChain) -> Self {
+        Buffers {
+            vtx_buffer: create_dynamic_buffer(
+                dasc,
+                INITIAL_VTX_CAPACITY * std::mem::size_of::<DrawVert>(),
+                D3D11_BIND_VERTEX_BUFFER,
+            ),
+            vtx_capacity: INITIAL_VTX_CAPACITY,
+            idx_buffer: create_dynamic_buffer(
+                dasc,
+                INITIAL_IDX_CAPACITY * std::mem::size_of::<DrawIdx>(),
+                D3D11_BIND_INDEX_BUFFER,
+            ),
+            idx_capacity: INITIAL_IDX_CAPACITY,
+            mtx_buffer: create_dynamic_buffer(dasc, std::mem::size_of::<ConstantBuffer>(), D3D11_BIND_CONSTANT_BUFFER),
+            filter_uniforms_buffer: None,
+        }
+    }
+
+    /// Writes the orthographic projection matrix for `[left, top, right, bottom]`
+    /// into `mtx_buffer`; callers bind it themselves via `mtx_buffer`.
+    pub fn set_constant_buffer(&mut self, dasc: &DeviceAndSwapChain, [l, t, r, b]: [f32; 4]) {
+        let mvp = [
+            [2. / (r - l), 0., 0., 0.],
+            [0., 2. / (t - b), 0., 0.],
+            [0., 0., 0.5, 0.],
+            [(r + l) / (l - r), (t + b) / (b - t), 0.5, 1.],
+        ];
+        write_dynamic_buffer(dasc, &self.mtx_buffer, &ConstantBuffer { mvp });
+    }
+
+    /// Writes `uniforms` into a second, lazily-created constant buffer used
+    /// by `FilterChain` passes, which need more than just an MVP matrix
+    /// (see `FilterUniforms`). Callers bind it themselves via
+    /// `filter_uniforms_buffer`, the same way the main HUD path binds
+    /// `mtx_buffer` explicitly.
+    pub fn set_filter_uniforms<T: Copy>(&mut self, dasc: &DeviceAndSwapChain, uniforms: T) {
+        let buffer = self
+            .filter_uniforms_buffer
+            .get_or_insert_with(|| create_dynamic_buffer(dasc, std::mem::size_of::<T>(), D3D11_BIND_CONSTANT_BUFFER));
+        write_dynamic_buffer(dasc, buffer, &uniforms);
+    }
+
+    pub fn filter_uniforms_buffer(&self) -> ID3D11Buffer {
+        self.filter_uniforms_buffer.clone().expect("set_filter_uniforms not called yet")
+    }
+
+    pub fn set_buffers(&mut self, dasc: &DeviceAndSwapChain, draw_lists: imgui::DrawListIterator<'_>) {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for draw_list in draw_lists {
+            vertices.extend_from_slice(draw_list.vtx_buffer());
+            indices.extend_from_slice(draw_list.idx_buffer());
+        }
+
+        if vertices.len() > self.vtx_capacity {
+            self.vtx_capacity = vertices.len() * 2;
+            self.vtx_buffer = create_dynamic_buffer(
+                dasc,
+                self.vtx_capacity * std::mem::size_of::<DrawVert>(),
+                D3D11_BIND_VERTEX_BUFFER,
+            );
+        }
+        if indices.len() > self.idx_capacity {
+            self.idx_capacity = indices.len() * 2;
+            self.idx_buffer = create_dynamic_buffer(
+                dasc,
+                self.idx_capacity * std::mem::size_of::<DrawIdx>(),
+                D3D11_BIND_INDEX_BUFFER,
+            );
+        }
+
+        write_dynamic_buffer_slice(dasc, &self.vtx_buffer, &vertices);
+        write_dynamic_buffer_slice(dasc, &self.idx_buffer, &indices);
+    }
+
+    pub fn vtx_buffer(&self) -> ID3D11Buffer {
+        self.vtx_buffer.clone()
+    }
+
+    pub fn idx_buffer(&self) -> Option<&ID3D11Buffer> {
+        Some(&self.idx_buffer)
+    }
+
+    pub fn mtx_buffer(&self) -> ID3D11Buffer {
+        self.mtx_buffer.clone()
+    }
+}
+
+fn create_dynamic_buffer(dasc: &DeviceAndSwapChain, size: usize, bind_flags: D3D11_BIND_FLAG) -> ID3D11Buffer {
+    let desc = D3D11_BUFFER_DESC {
+        ByteWidth: size.max(1) as u32,
+        Usage: D3D11_USAGE_DYNAMIC,
+        BindFlags: bind_flags.0 as u32,
+        CPUAccessFlags: D3D11_CPU_ACCESS_WRITE.0 as u32,
+        ..Default::default()
+    };
+    let mut buffer = None;
+    unsafe { dasc.dev().CreateBuffer(&desc, None, Some(&mut buffer)) }.expect("CreateBuffer (dynamic)");
+    buffer.expect("dynamic buffer")
+}
+
+fn write_dynamic_buffer<T: Copy>(dasc: &DeviceAndSwapChain, buffer: &ID3D11Buffer, value: &T) {
+    write_dynamic_buffer_slice(dasc, buffer, std::slice::from_ref(value));
+}
+
+fn write_dynamic_buffer_slice<T: Copy>(dasc: &DeviceAndSwapChain, buffer: &ID3D11Buffer, values: &[T]) {
+    if values.is_empty() {
+        return;
+    }
+    unsafe {
+        let dev_ctx = dasc.dev_ctx();
+        let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+        dev_ctx.Map(buffer, 0, D3D11_MAP_WRITE_DISCARD, 0, Some(&mut mapped)).expect("Map dynamic buffer");
+        std::ptr::copy_nonoverlapping(values.as_ptr(), mapped.pData as *mut T, values.len());
+        dev_ctx.Unmap(buffer, 0);
+    }
+}
@@ -0,0 +1,157 @@
+use imgui::{DrawIdx, DrawVert};
+use windows::Win32::Graphics::Direct3D12::*;
+use windows::Win32::Graphics::Dxgi::Common::{DXGI_FORMAT_R16_UINT, DXGI_FORMAT_R32_UINT};
+
+const INITIAL_VTX_CAPACITY: usize = 5000;
+const INITIAL_IDX_CAPACITY: usize = 10000;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ConstantBuffer {
+    mvp: [[f32; 4]; 4],
+}
+
+/// A single frame's upload-heap-backed vertex/index/constant buffers.
+///
+/// Unlike the D3D11 `Buffers`, which maps one device-owned buffer per
+/// frame, every in-flight frame here needs its own upload allocation:
+/// the GPU may still be reading frame N-1's buffers while the CPU is
+/// already writing frame N's.
+struct FrameBuffers {
+    vtx_buffer: ID3D12Resource,
+    vtx_capacity: usize,
+    idx_buffer: ID3D12Resource,
+    idx_capacity: usize,
+    constant_buffer: ID3D12Resource,
+}
+
+/// Holds one set of upload buffers per swap-chain buffer; `buffer_count`
+/// must match the swap chain's actual `BufferCount`, since hudhook attaches
+/// to swap chains it didn't create.
+pub struct Buffers12 {
+    frames: Vec<FrameBuffers>,
+}
+
+impl Buffers12 {
+    pub fn new(dev: &ID3D12Device, buffer_count: usize) -> Self {
+        Buffers12 {
+            frames: (0..buffer_count)
+                .map(|_| FrameBuffers {
+                    vtx_buffer: create_upload_buffer(dev, INITIAL_VTX_CAPACITY * std::mem::size_of::<DrawVert>()),
+                    vtx_capacity: INITIAL_VTX_CAPACITY,
+                    idx_buffer: create_upload_buffer(dev, INITIAL_IDX_CAPACITY * std::mem::size_of::<DrawIdx>()),
+                    idx_capacity: INITIAL_IDX_CAPACITY,
+                    constant_buffer: create_upload_buffer(dev, std::mem::size_of::<ConstantBuffer>()),
+                })
+                .collect(),
+        }
+    }
+
+    /// Mirrors `Buffers::set_constant_buffer`: writes the orthographic
+    /// projection matrix for the current display rect into this frame's
+    /// constant buffer.
+    pub fn set_constant_buffer(&mut self, frame_index: usize, [l, t, r, b]: [f32; 4]) {
+        let mvp = [
+            [2. / (r - l), 0., 0., 0.],
+            [0., 2. / (t - b), 0., 0.],
+            [0., 0., 0.5, 0.],
+            [(r + l) / (l - r), (t + b) / (b - t), 0.5, 1.],
+        ];
+        write_upload_buffer(&self.frames[frame_index].constant_buffer, &ConstantBuffer { mvp });
+    }
+
+    /// Mirrors `Buffers::set_buffers`: flattens every draw list's vertex
+    /// and index data into this frame's upload buffers, growing them
+    /// first if the frame needs more room than they currently hold.
+    pub fn set_buffers(&mut self, dev: &ID3D12Device, frame_index: usize, draw_lists: imgui::DrawListIterator<'_>) {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for draw_list in draw_lists {
+            vertices.extend_from_slice(draw_list.vtx_buffer());
+            indices.extend_from_slice(draw_list.idx_buffer());
+        }
+
+        let frame = &mut self.frames[frame_index];
+        if vertices.len() > frame.vtx_capacity {
+            frame.vtx_capacity = vertices.len() * 2;
+            frame.vtx_buffer = create_upload_buffer(dev, frame.vtx_capacity * std::mem::size_of::<DrawVert>());
+        }
+        if indices.len() > frame.idx_capacity {
+            frame.idx_capacity = indices.len() * 2;
+            frame.idx_buffer = create_upload_buffer(dev, frame.idx_capacity * std::mem::size_of::<DrawIdx>());
+        }
+
+        write_upload_buffer_slice(&frame.vtx_buffer, &vertices);
+        write_upload_buffer_slice(&frame.idx_buffer, &indices);
+    }
+
+    pub fn vertex_buffer_view(&self, frame_index: usize) -> D3D12_VERTEX_BUFFER_VIEW {
+        let frame = &self.frames[frame_index];
+        D3D12_VERTEX_BUFFER_VIEW {
+            BufferLocation: unsafe { frame.vtx_buffer.GetGPUVirtualAddress() },
+            SizeInBytes: (frame.vtx_capacity * std::mem::size_of::<DrawVert>()) as u32,
+            StrideInBytes: std::mem::size_of::<DrawVert>() as u32,
+        }
+    }
+
+    pub fn index_buffer_view(&self, frame_index: usize) -> D3D12_INDEX_BUFFER_VIEW {
+        let frame = &self.frames[frame_index];
+        D3D12_INDEX_BUFFER_VIEW {
+            BufferLocation: unsafe { frame.idx_buffer.GetGPUVirtualAddress() },
+            SizeInBytes: (frame.idx_capacity * std::mem::size_of::<DrawIdx>()) as u32,
+            Format: if std::mem::size_of::<DrawIdx>() == 2 {
+                DXGI_FORMAT_R16_UINT
+            } else {
+                DXGI_FORMAT_R32_UINT
+            },
+        }
+    }
+
+    pub fn constant_buffer_address(&self, frame_index: usize) -> u64 {
+        unsafe { self.frames[frame_index].constant_buffer.GetGPUVirtualAddress() }
+    }
+}
+
+fn create_upload_buffer(dev: &ID3D12Device, size: usize) -> ID3D12Resource {
+    let heap_props = D3D12_HEAP_PROPERTIES { Type: D3D12_HEAP_TYPE_UPLOAD, ..Default::default() };
+    let desc = D3D12_RESOURCE_DESC {
+        Dimension: D3D12_RESOURCE_DIMENSION_BUFFER,
+        Width: size.max(1) as u64,
+        Height: 1,
+        DepthOrArraySize: 1,
+        MipLevels: 1,
+        SampleDesc: windows::Win32::Graphics::Dxgi::Common::DXGI_SAMPLE_DESC { Count: 1, Quality: 0 },
+        Layout: D3D12_TEXTURE_LAYOUT_ROW_MAJOR,
+        ..Default::default()
+    };
+
+    let mut resource: Option<ID3D12Resource> = None;
+    unsafe {
+        dev.CreateCommittedResource(
+            &heap_props,
+            D3D12_HEAP_FLAG_NONE,
+            &desc,
+            D3D12_RESOURCE_STATE_GENERIC_READ,
+            None,
+            &mut resource,
+        )
+    }
+    .expect("CreateCommittedResource (upload)");
+    resource.expect("upload resource")
+}
+
+fn write_upload_buffer<T: Copy>(resource: &ID3D12Resource, value: &T) {
+    write_upload_buffer_slice(resource, std::slice::from_ref(value));
+}
+
+fn write_upload_buffer_slice<T: Copy>(resource: &ID3D12Resource, values: &[T]) {
+    if values.is_empty() {
+        return;
+    }
+    unsafe {
+        let mut mapped = std::ptr::null_mut();
+        resource.Map(0, None, Some(&mut mapped)).expect("Map upload buffer");
+        std::ptr::copy_nonoverlapping(values.as_ptr(), mapped as *mut T, values.len());
+        resource.Unmap(0, None);
+    }
+}
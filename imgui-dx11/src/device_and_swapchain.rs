@@ -0,0 +1,216 @@
+use windows::Win32::Foundation::{HWND, RECT};
+use windows::Win32::Graphics::Direct3D::D3D_DRIVER_TYPE_HARDWARE;
+use windows::Win32::Graphics::Direct3D11::*;
+use windows::Win32::Graphics::Dxgi::Common::*;
+use windows::Win32::Graphics::Dxgi::*;
+
+/// Wraps the device/context/swap chain plus the back-buffer render target
+/// view and an SRV-bound copy of the back buffer `FilterChain` passes
+/// sample from (the swap chain's own back buffer isn't created with
+/// `D3D11_BIND_SHADER_RESOURCE`, so it can't be sampled directly).
+pub struct DeviceAndSwapChain {
+    dev: ID3D11Device,
+    dev_ctx: ID3D11DeviceContext,
+    swap_chain: IDXGISwapChain,
+    render_target: ID3D11RenderTargetView,
+    back_buffer_copy: ID3D11Texture2D,
+    back_buffer_srv: ID3D11ShaderResourceView,
+    back_buffer_desc: D3D11_TEXTURE2D_DESC,
+    fullscreen_triangle: ID3D11Buffer,
+}
+
+/// Vertex layout shared with [`crate::shader_program::ShaderProgram`]'s
+/// input layout (`POSITION`/`TEXCOORD0`/`COLOR0`).
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct FullscreenVertex {
+    pos: [f32; 2],
+    uv: [f32; 2],
+    col: u32,
+}
+
+impl DeviceAndSwapChain {
+    /// Creates its own device, context and swap chain for `hwnd`, for
+    /// standalone use outside a hooked `Present`.
+    pub fn new(hwnd: HWND) -> Self {
+        let swap_chain_desc = DXGI_SWAP_CHAIN_DESC {
+            BufferDesc: DXGI_MODE_DESC { Format: DXGI_FORMAT_R8G8B8A8_UNORM, ..Default::default() },
+            SampleDesc: DXGI_SAMPLE_DESC { Count: 1, Quality: 0 },
+            BufferUsage: DXGI_USAGE_RENDER_TARGET_OUTPUT,
+            BufferCount: 2,
+            OutputWindow: hwnd,
+            Windowed: true.into(),
+            SwapEffect: DXGI_SWAP_EFFECT_DISCARD,
+            ..Default::default()
+        };
+
+        let mut dev = None;
+        let mut dev_ctx = None;
+        let mut swap_chain = None;
+        unsafe {
+            D3D11CreateDeviceAndSwapChain(
+                None,
+                D3D_DRIVER_TYPE_HARDWARE,
+                None,
+                D3D11_CREATE_DEVICE_FLAG(0),
+                None,
+                D3D11_SDK_VERSION,
+                Some(&swap_chain_desc),
+                Some(&mut swap_chain),
+                Some(&mut dev),
+                None,
+                Some(&mut dev_ctx),
+            )
+        }
+        .expect("D3D11CreateDeviceAndSwapChain");
+
+        Self::new_with_ptrs(dev.expect("device"), dev_ctx.expect("device context"), swap_chain.expect("swap chain"))
+    }
+
+    /// Wraps a device/context/swap chain hudhook intercepted from the
+    /// hooked game's own `Present` call.
+    pub fn new_with_ptrs(dev: ID3D11Device, dev_ctx: ID3D11DeviceContext, swap_chain: IDXGISwapChain) -> Self {
+        let (render_target, back_buffer_desc) = create_render_target_view(&dev, &swap_chain);
+        let (back_buffer_copy, back_buffer_srv) = create_back_buffer_copy(&dev, &back_buffer_desc);
+        let fullscreen_triangle = create_fullscreen_triangle(&dev);
+        DeviceAndSwapChain {
+            dev,
+            dev_ctx,
+            swap_chain,
+            render_target,
+            back_buffer_copy,
+            back_buffer_srv,
+            back_buffer_desc,
+            fullscreen_triangle,
+        }
+    }
+
+    pub fn dev(&self) -> ID3D11Device {
+        self.dev.clone()
+    }
+
+    pub fn dev_ctx(&self) -> ID3D11DeviceContext {
+        self.dev_ctx.clone()
+    }
+
+    pub fn swap_chain(&self) -> IDXGISwapChain {
+        self.swap_chain.clone()
+    }
+
+    pub fn get_window_rect(&self) -> Option<RECT> {
+        Some(RECT { left: 0, top: 0, right: self.back_buffer_desc.Width as i32, bottom: self.back_buffer_desc.Height as i32 })
+    }
+
+    pub fn set_viewport(&self, dev_ctx: &ID3D11DeviceContext, rect: RECT) {
+        let viewport = D3D11_VIEWPORT {
+            TopLeftX: rect.left as f32,
+            TopLeftY: rect.top as f32,
+            Width: (rect.right - rect.left) as f32,
+            Height: (rect.bottom - rect.top) as f32,
+            MinDepth: 0.,
+            MaxDepth: 1.,
+        };
+        unsafe { dev_ctx.RSSetViewports(Some(&[viewport])) };
+    }
+
+    /// Binds the swap chain's own back buffer as the current render
+    /// target, with no depth/stencil view.
+    pub fn set_render_target(&self, dev_ctx: &ID3D11DeviceContext) {
+        unsafe { dev_ctx.OMSetRenderTargets(Some(&[Some(self.render_target.clone())]), None) };
+    }
+
+    /// Re-applies the viewport and render target `DrawCmd::ResetRenderState`
+    /// expects after a `RawCallback` that may have clobbered them, using
+    /// `draw_data`'s display rect. Takes `dev_ctx` explicitly rather than
+    /// always targeting the immediate context, so a reset mid-recording
+    /// lands on whichever context is actually building the current frame
+    /// (see `RenderEngine::render`).
+    pub fn setup_state(&self, dev_ctx: &ID3D11DeviceContext, draw_data: &imgui::DrawData) {
+        let [x, y] = draw_data.display_pos;
+        let [width, height] = draw_data.display_size;
+        self.set_viewport(dev_ctx, RECT { left: x as i32, top: y as i32, right: (x + width) as i32, bottom: (y + height) as i32 });
+        self.set_render_target(dev_ctx);
+    }
+
+    /// Copies the current back buffer (with the HUD already drawn into it)
+    /// into an SRV-bound texture, so a `FilterChain` can sample it as the
+    /// first pass's input.
+    pub fn back_buffer_srv(&self) -> ID3D11ShaderResourceView {
+        unsafe {
+            let back_buffer: ID3D11Texture2D = self.swap_chain.GetBuffer(0).expect("GetBuffer");
+            self.dev_ctx.CopyResource(&self.back_buffer_copy, &back_buffer);
+        }
+        self.back_buffer_srv.clone()
+    }
+
+    /// Binds `srv` as pixel-shader resource slot 0, for a `FilterChain`
+    /// pass sampling the previous pass's output (or the original frame).
+    pub fn set_shader_resources(&self, srv: ID3D11ShaderResourceView) {
+        unsafe { self.dev_ctx.PSSetShaderResources(0, &[Some(srv)]) };
+    }
+
+    /// Draws a `FilterChain` pass's full-screen triangle: 3 vertices
+    /// already in clip space (the standard "big triangle" trick, clipped
+    /// to the viewport by the rasterizer), so every pass covers its whole
+    /// render target regardless of resolution without a per-size vertex
+    /// buffer.
+    pub fn draw_fullscreen_triangle(&self) {
+        unsafe {
+            self.dev_ctx.IASetVertexBuffers(
+                0,
+                1,
+                &Some(self.fullscreen_triangle.clone()),
+                &(std::mem::size_of::<FullscreenVertex>() as u32),
+                &0,
+            );
+            self.dev_ctx.IASetIndexBuffer(None, DXGI_FORMAT_UNKNOWN, 0);
+            self.dev_ctx.IASetPrimitiveTopology(windows::Win32::Graphics::Direct3D::D3D11_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+            self.dev_ctx.Draw(3, 0);
+        }
+    }
+}
+
+fn create_render_target_view(dev: &ID3D11Device, swap_chain: &IDXGISwapChain) -> (ID3D11RenderTargetView, D3D11_TEXTURE2D_DESC) {
+    let back_buffer: ID3D11Texture2D = unsafe { swap_chain.GetBuffer(0) }.expect("GetBuffer");
+    let mut desc = D3D11_TEXTURE2D_DESC::default();
+    unsafe { back_buffer.GetDesc(&mut desc) };
+
+    let mut render_target = None;
+    unsafe { dev.CreateRenderTargetView(&back_buffer, None, Some(&mut render_target)) }.expect("CreateRenderTargetView");
+    (render_target.expect("render target view"), desc)
+}
+
+fn create_fullscreen_triangle(dev: &ID3D11Device) -> ID3D11Buffer {
+    let vertices = [
+        FullscreenVertex { pos: [-1., 1.], uv: [0., 0.], col: u32::MAX },
+        FullscreenVertex { pos: [3., 1.], uv: [2., 0.], col: u32::MAX },
+        FullscreenVertex { pos: [-1., -3.], uv: [0., 2.], col: u32::MAX },
+    ];
+
+    let desc = D3D11_BUFFER_DESC {
+        ByteWidth: std::mem::size_of_val(&vertices) as u32,
+        Usage: D3D11_USAGE_IMMUTABLE,
+        BindFlags: D3D11_BIND_VERTEX_BUFFER.0 as u32,
+        ..Default::default()
+    };
+    let initial_data = D3D11_SUBRESOURCE_DATA { pSysMem: vertices.as_ptr() as _, ..Default::default() };
+
+    let mut buffer = None;
+    unsafe { dev.CreateBuffer(&desc, Some(&initial_data), Some(&mut buffer)) }.expect("CreateBuffer (fullscreen triangle)");
+    buffer.expect("fullscreen triangle buffer")
+}
+
+fn create_back_buffer_copy(dev: &ID3D11Device, back_buffer_desc: &D3D11_TEXTURE2D_DESC) -> (ID3D11Texture2D, ID3D11ShaderResourceView) {
+    let desc =
+        D3D11_TEXTURE2D_DESC { BindFlags: D3D11_BIND_SHADER_RESOURCE.0 as u32, MiscFlags: 0, ..*back_buffer_desc };
+
+    let mut texture = None;
+    unsafe { dev.CreateTexture2D(&desc, None, Some(&mut texture)) }.expect("CreateTexture2D (back buffer copy)");
+    let texture = texture.expect("back buffer copy texture");
+
+    let mut srv = None;
+    unsafe { dev.CreateShaderResourceView(&texture, None, Some(&mut srv)) }
+        .expect("CreateShaderResourceView (back buffer copy)");
+
+    (texture, srv.expect("back buffer SRV"))
+}
@@ -0,0 +1,296 @@
+use windows::Win32::Foundation::{CloseHandle, HANDLE, RECT};
+use windows::Win32::Graphics::Direct3D12::*;
+use windows::Win32::Graphics::Dxgi::Common::DXGI_FORMAT;
+use windows::Win32::Graphics::Dxgi::{
+    IDXGIFactory5, IDXGISwapChain3, DXGI_FEATURE_PRESENT_ALLOW_TEARING, DXGI_SWAP_CHAIN_DESC,
+    DXGI_SWAP_EFFECT_FLIP_DISCARD,
+};
+use windows::Win32::System::Threading::{CreateEventW, WaitForSingleObject, INFINITE};
+
+/// D3D12 has no immediate context: submission happens through a command
+/// queue, so this additionally owns the queue, a fence, and one command
+/// allocator per swap-chain buffer, plus the RTV heap backing each of the
+/// swap chain's own buffers (sized off the swap chain's actual
+/// `BufferCount`, since hudhook attaches to swap chains it didn't create).
+pub struct DeviceAndSwapChain12 {
+    dev: ID3D12Device,
+    command_queue: ID3D12CommandQueue,
+    swap_chain: IDXGISwapChain3,
+    rtv_heap: ID3D12DescriptorHeap,
+    rtv_descriptor_size: u32,
+    rtv_format: DXGI_FORMAT,
+    back_buffers: Vec<ID3D12Resource>,
+    command_allocators: Vec<ID3D12CommandAllocator>,
+    command_list: ID3D12GraphicsCommandList,
+    fence: ID3D12Fence,
+    fence_event: HANDLE,
+    fence_values: Vec<u64>,
+    next_fence_value: u64,
+    allow_tearing: bool,
+}
+
+impl DeviceAndSwapChain12 {
+    pub fn new_with_ptrs(
+        dev: ID3D12Device,
+        command_queue: ID3D12CommandQueue,
+        swap_chain: IDXGISwapChain3,
+    ) -> Self {
+        let desc = unsafe { swap_chain.GetDesc() }.expect("GetDesc");
+        let buffer_count = desc.BufferCount.max(1) as usize;
+
+        let (rtv_heap, rtv_descriptor_size, back_buffers) =
+            create_render_targets(&dev, &swap_chain, buffer_count, desc.BufferDesc.Format);
+        let command_allocators = create_command_allocators(&dev, buffer_count);
+        let command_list = create_command_list(&dev, &command_allocators[0]);
+        let fence = create_fence(&dev);
+        let fence_event = create_fence_event();
+        let allow_tearing = supports_tearing(&swap_chain, desc.SwapEffect);
+
+        DeviceAndSwapChain12 {
+            dev,
+            command_queue,
+            swap_chain,
+            rtv_heap,
+            rtv_descriptor_size,
+            rtv_format: desc.BufferDesc.Format,
+            back_buffers,
+            command_allocators,
+            command_list,
+            fence,
+            fence_event,
+            fence_values: vec![0; buffer_count],
+            next_fence_value: 0,
+            allow_tearing,
+        }
+    }
+
+    pub fn dev(&self) -> ID3D12Device {
+        self.dev.clone()
+    }
+
+    /// Whether the adapter supports `DXGI_PRESENT_ALLOW_TEARING` *and* the
+    /// swap chain was created flip-model (`DXGI_SWAP_EFFECT_FLIP_DISCARD`);
+    /// tearing is only a valid `Present` flag combination for flip-model
+    /// swap chains, checked once at construction time.
+    pub fn allow_tearing(&self) -> bool {
+        self.allow_tearing
+    }
+
+    pub fn rtv_format(&self) -> DXGI_FORMAT {
+        self.rtv_format
+    }
+
+    /// The swap chain's actual buffer count, i.e. the number of frames
+    /// that may be in flight at once.
+    pub fn buffer_count(&self) -> usize {
+        self.back_buffers.len()
+    }
+
+    pub fn command_queue(&self) -> ID3D12CommandQueue {
+        self.command_queue.clone()
+    }
+
+    pub fn swap_chain(&self) -> IDXGISwapChain3 {
+        self.swap_chain.clone()
+    }
+
+    pub fn command_list(&self) -> ID3D12GraphicsCommandList {
+        self.command_list.clone()
+    }
+
+    pub fn frame_index(&self) -> usize {
+        unsafe { self.swap_chain.GetCurrentBackBufferIndex() as usize }
+    }
+
+    pub fn get_window_rect(&self) -> Option<RECT> {
+        let desc: DXGI_SWAP_CHAIN_DESC = unsafe { self.swap_chain.GetDesc() }.ok()?;
+        Some(RECT {
+            left: 0,
+            top: 0,
+            right: desc.BufferDesc.Width as i32,
+            bottom: desc.BufferDesc.Height as i32,
+        })
+    }
+
+    fn rtv_handle(&self, frame_index: usize) -> D3D12_CPU_DESCRIPTOR_HANDLE {
+        let mut handle = unsafe { self.rtv_heap.GetCPUDescriptorHandleForHeapStart() };
+        handle.ptr += frame_index * self.rtv_descriptor_size as usize;
+        handle
+    }
+
+    /// Waits for the GPU to be done with `frame_index`'s allocator, resets
+    /// it and reopens the shared command list against it, transitions that
+    /// back buffer from `PRESENT` to `RENDER_TARGET`, and binds it (with a
+    /// matching viewport) as the current render target.
+    pub fn begin_frame(&self, frame_index: usize, viewport_size: (f32, f32)) {
+        unsafe {
+            self.wait_for_frame(frame_index);
+            self.command_allocators[frame_index].Reset().expect("Reset command allocator");
+            self.command_list
+                .Reset(&self.command_allocators[frame_index], None)
+                .expect("Reset command list");
+
+            self.command_list.ResourceBarrier(&[transition_barrier(
+                &self.back_buffers[frame_index],
+                D3D12_RESOURCE_STATE_PRESENT,
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+            )]);
+
+            let rtv = self.rtv_handle(frame_index);
+            self.command_list.OMSetRenderTargets(1, Some(&rtv), false, None);
+            self.command_list.RSSetViewports(&[D3D12_VIEWPORT {
+                TopLeftX: 0.,
+                TopLeftY: 0.,
+                Width: viewport_size.0,
+                Height: viewport_size.1,
+                MinDepth: 0.,
+                MaxDepth: 1.,
+            }]);
+        }
+    }
+
+    /// Transitions `frame_index`'s back buffer back to `PRESENT`, closes
+    /// and submits the command list, then signals the next value from a
+    /// single monotonically increasing fence counter shared across every
+    /// slot — using one counter per slot independently would let two
+    /// slots signal the same value and let `begin_frame` reset an
+    /// allocator the GPU is still using.
+    pub fn end_frame(&mut self, frame_index: usize) {
+        unsafe {
+            self.command_list.ResourceBarrier(&[transition_barrier(
+                &self.back_buffers[frame_index],
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+                D3D12_RESOURCE_STATE_PRESENT,
+            )]);
+            self.command_list.Close().expect("Close command list");
+            let lists = [Some(self.command_list.cast().expect("ID3D12CommandList"))];
+            self.command_queue.ExecuteCommandLists(&lists);
+
+            self.next_fence_value += 1;
+            self.command_queue.Signal(&self.fence, self.next_fence_value).expect("Signal fence");
+            self.fence_values[frame_index] = self.next_fence_value;
+        }
+    }
+
+    fn wait_for_frame(&self, frame_index: usize) {
+        let target = self.fence_values[frame_index];
+        if target == 0 {
+            return;
+        }
+        unsafe {
+            if self.fence.GetCompletedValue() < target {
+                self.fence.SetEventOnCompletion(target, self.fence_event).expect("SetEventOnCompletion");
+                WaitForSingleObject(self.fence_event, INFINITE);
+            }
+        }
+    }
+}
+
+impl Drop for DeviceAndSwapChain12 {
+    fn drop(&mut self) {
+        unsafe { CloseHandle(self.fence_event) };
+    }
+}
+
+fn transition_barrier(
+    resource: &ID3D12Resource,
+    before: D3D12_RESOURCE_STATES,
+    after: D3D12_RESOURCE_STATES,
+) -> D3D12_RESOURCE_BARRIER {
+    D3D12_RESOURCE_BARRIER {
+        Type: D3D12_RESOURCE_BARRIER_TYPE_TRANSITION,
+        Flags: D3D12_RESOURCE_BARRIER_FLAG_NONE,
+        Anonymous: D3D12_RESOURCE_BARRIER_0 {
+            Transition: std::mem::ManuallyDrop::new(D3D12_RESOURCE_TRANSITION_BARRIER {
+                pResource: windows::core::ManuallyDrop::new(Some(resource.clone())),
+                Subresource: D3D12_RESOURCE_BARRIER_ALL_SUBRESOURCES,
+                StateBefore: before,
+                StateAfter: after,
+            }),
+        },
+    }
+}
+
+fn create_render_targets(
+    dev: &ID3D12Device,
+    swap_chain: &IDXGISwapChain3,
+    buffer_count: usize,
+    format: DXGI_FORMAT,
+) -> (ID3D12DescriptorHeap, u32, Vec<ID3D12Resource>) {
+    let heap_desc = D3D12_DESCRIPTOR_HEAP_DESC {
+        Type: D3D12_DESCRIPTOR_HEAP_TYPE_RTV,
+        NumDescriptors: buffer_count as u32,
+        Flags: D3D12_DESCRIPTOR_HEAP_FLAG_NONE,
+        NodeMask: 0,
+    };
+    let rtv_heap: ID3D12DescriptorHeap =
+        unsafe { dev.CreateDescriptorHeap(&heap_desc) }.expect("CreateDescriptorHeap (RTV)");
+    let rtv_descriptor_size = unsafe { dev.GetDescriptorHandleIncrementSize(D3D12_DESCRIPTOR_HEAP_TYPE_RTV) };
+
+    let mut handle = unsafe { rtv_heap.GetCPUDescriptorHandleForHeapStart() };
+    let rtv_desc = D3D12_RENDER_TARGET_VIEW_DESC {
+        Format: format,
+        ViewDimension: D3D12_RTV_DIMENSION_TEXTURE2D,
+        ..Default::default()
+    };
+
+    let back_buffers = (0..buffer_count)
+        .map(|i| {
+            let back_buffer: ID3D12Resource =
+                unsafe { swap_chain.GetBuffer(i as u32) }.expect("GetBuffer");
+            unsafe { dev.CreateRenderTargetView(&back_buffer, Some(&rtv_desc), handle) };
+            handle.ptr += rtv_descriptor_size as usize;
+            back_buffer
+        })
+        .collect();
+
+    (rtv_heap, rtv_descriptor_size, back_buffers)
+}
+
+fn create_command_allocators(dev: &ID3D12Device, buffer_count: usize) -> Vec<ID3D12CommandAllocator> {
+    (0..buffer_count)
+        .map(|_| unsafe {
+            dev.CreateCommandAllocator(D3D12_COMMAND_LIST_TYPE_DIRECT).expect("CreateCommandAllocator")
+        })
+        .collect()
+}
+
+fn create_command_list(dev: &ID3D12Device, allocator: &ID3D12CommandAllocator) -> ID3D12GraphicsCommandList {
+    let command_list: ID3D12GraphicsCommandList =
+        unsafe { dev.CreateCommandList(0, D3D12_COMMAND_LIST_TYPE_DIRECT, allocator, None) }
+            .expect("CreateCommandList");
+    unsafe { command_list.Close() }.expect("Close command list");
+    command_list
+}
+
+fn create_fence(dev: &ID3D12Device) -> ID3D12Fence {
+    unsafe { dev.CreateFence(0, D3D12_FENCE_FLAG_NONE) }.expect("CreateFence")
+}
+
+fn create_fence_event() -> HANDLE {
+    unsafe { CreateEventW(None, false, false, None) }.expect("CreateEventW")
+}
+
+fn supports_tearing(
+    swap_chain: &IDXGISwapChain3,
+    swap_effect: windows::Win32::Graphics::Dxgi::DXGI_SWAP_EFFECT,
+) -> bool {
+    if swap_effect != DXGI_SWAP_EFFECT_FLIP_DISCARD {
+        return false;
+    }
+
+    let Ok(factory) = (unsafe { swap_chain.GetParent::<IDXGIFactory5>() }) else {
+        return false;
+    };
+    let mut allow_tearing = windows::Win32::Foundation::BOOL(0);
+    unsafe {
+        factory
+            .CheckFeatureSupport(
+                DXGI_FEATURE_PRESENT_ALLOW_TEARING,
+                &mut allow_tearing as *mut _ as *mut _,
+                std::mem::size_of_val(&allow_tearing) as u32,
+            )
+            .is_ok()
+            && allow_tearing.as_bool()
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use windows::Win32::Graphics::Direct3D11::{
+    ID3D11SamplerState, ID3D11ShaderResourceView, D3D11_FILTER_MIN_MAG_MIP_LINEAR,
+    D3D11_SAMPLER_DESC, D3D11_TEXTURE_ADDRESS_CLAMP,
+};
+
+use crate::buffers::Buffers;
+use crate::device_and_swapchain::DeviceAndSwapChain;
+use crate::shader_program::ShaderProgram;
+use crate::texture::Texture;
+
+/// Per-frame uniforms every pass shader receives, matching the standard
+/// RetroArch/slang semantics (`MVP`, `SourceSize`, `OutputSize`, `FrameCount`).
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct FilterUniforms {
+    mvp: [[f32; 4]; 4],
+    source_size: [f32; 4],
+    output_size: [f32; 4],
+    frame_count: u32,
+    _pad: [u32; 3],
+}
+
+/// One pass of a `.slangp` preset: its compiled shader, sampler, and the
+/// intermediate render target its output is written to.
+struct FilterPass {
+    shader_program: ShaderProgram,
+    sampler: ID3D11SamplerState,
+    render_target: Texture,
+    scale_x: f32,
+    scale_y: f32,
+}
+
+/// Runs a RetroArch-style `.slangp` filter preset (CRT/scanline/upscale
+/// passes, etc.) over the finished frame.
+///
+/// A `FilterChain` is loaded once from a preset file and reused across
+/// frames; each pass's framebuffer is allocated at load time and resized
+/// lazily if the window size it was built for changes.
+pub struct FilterChain {
+    passes: Vec<FilterPass>,
+    buffers: Buffers,
+    size: (u32, u32),
+}
+
+impl FilterChain {
+    /// Parses `preset_path` (a `.slangp` file) and compiles every pass it
+    /// references. Returns an error if the preset can't be read or parsed,
+    /// or if a pass fails to compile; with no chain loaded,
+    /// `RenderEngine::render` skips post-processing entirely.
+    pub fn load(dasc: &DeviceAndSwapChain, preset_path: &Path, size: (u32, u32)) -> Result<Self, String> {
+        let preset = PresetConfig::parse(preset_path)?;
+
+        let mut passes = Vec::with_capacity(preset.shaders.len());
+        for shader in &preset.shaders {
+            let shader_program = ShaderProgram::new_from_source(dasc, &shader.source)
+                .map_err(|e| format!("compiling {}: {e}", shader.source.display()))?;
+            let sampler = create_sampler(dasc)?;
+            let render_target = Texture::new_render_target(
+                dasc,
+                (size.0 as f32 * shader.scale_x) as u32,
+                (size.1 as f32 * shader.scale_y) as u32,
+            )
+            .map_err(|e| format!("allocating render target for {}: {e}", shader.source.display()))?;
+
+            passes.push(FilterPass {
+                shader_program,
+                sampler,
+                render_target,
+                scale_x: shader.scale_x,
+                scale_y: shader.scale_y,
+            });
+        }
+
+        Ok(FilterChain { passes, buffers: Buffers::new(dasc), size })
+    }
+
+    /// Runs every pass in order: pass 0 samples `input`, each subsequent
+    /// pass samples the previous pass's render target, and the final pass
+    /// writes to the real swap-chain render target (sized `output_size`)
+    /// instead of an intermediate texture.
+    pub fn render(
+        &mut self,
+        dasc: &DeviceAndSwapChain,
+        input: ID3D11ShaderResourceView,
+        output_size: (u32, u32),
+        frame_count: u32,
+    ) -> Result<(), String> {
+        if self.passes.is_empty() {
+            return Ok(());
+        }
+        if self.size != output_size {
+            self.resize(dasc, output_size)?;
+        }
+
+        let mut source = input;
+        let mut source_size = (output_size.0 as f32, output_size.1 as f32);
+        let last = self.passes.len() - 1;
+        for (i, pass) in self.passes.iter().enumerate() {
+            let target_size = if i == last {
+                output_size
+            } else {
+                ((output_size.0 as f32 * pass.scale_x) as u32, (output_size.1 as f32 * pass.scale_y) as u32)
+            };
+            let target_size_f = (target_size.0 as f32, target_size.1 as f32);
+
+            self.buffers.set_filter_uniforms(
+                dasc,
+                FilterUniforms {
+                    // Every pass draws a full-screen triangle already in
+                    // clip space (see `DeviceAndSwapChain::draw_fullscreen_triangle`),
+                    // so unlike the main HUD quad's MVP there's no
+                    // per-resolution projection to apply here; identity
+                    // matches standard RetroArch/slang pass conventions.
+                    mvp: IDENTITY_MVP,
+                    source_size: [source_size.0, source_size.1, 1. / source_size.0, 1. / source_size.1],
+                    output_size: [target_size_f.0, target_size_f.1, 1. / target_size_f.0, 1. / target_size_f.1],
+                    frame_count,
+                    _pad: [0; 3],
+                },
+            );
+
+            if i == last {
+                dasc.set_render_target(&dasc.dev_ctx());
+            } else {
+                pass.render_target.set_as_render_target(dasc);
+            }
+
+            unsafe {
+                pass.shader_program.set_state(&dasc.dev_ctx());
+                dasc.set_shader_resources(source);
+                dasc.dev_ctx().PSSetSamplers(0, &[Some(pass.sampler.clone())]);
+                dasc.dev_ctx().VSSetConstantBuffers(0, &[Some(self.buffers.filter_uniforms_buffer())]);
+                dasc.dev_ctx().PSSetConstantBuffers(0, &[Some(self.buffers.filter_uniforms_buffer())]);
+                dasc.draw_fullscreen_triangle();
+            }
+
+            source = pass.render_target.tex_view();
+            source_size = target_size_f;
+        }
+
+        Ok(())
+    }
+
+    fn resize(&mut self, dasc: &DeviceAndSwapChain, size: (u32, u32)) -> Result<(), String> {
+        for pass in &mut self.passes {
+            pass.render_target = Texture::new_render_target(
+                dasc,
+                (size.0 as f32 * pass.scale_x) as u32,
+                (size.1 as f32 * pass.scale_y) as u32,
+            )
+            .map_err(|e| format!("resizing filter chain render target: {e}"))?;
+        }
+        self.size = size;
+        Ok(())
+    }
+}
+
+const IDENTITY_MVP: [[f32; 4]; 4] =
+    [[1., 0., 0., 0.], [0., 1., 0., 0.], [0., 0., 1., 0.], [0., 0., 0., 1.]];
+
+fn create_sampler(dasc: &DeviceAndSwapChain) -> Result<ID3D11SamplerState, String> {
+    let desc = D3D11_SAMPLER_DESC {
+        Filter: D3D11_FILTER_MIN_MAG_MIP_LINEAR,
+        AddressU: D3D11_TEXTURE_ADDRESS_CLAMP,
+        AddressV: D3D11_TEXTURE_ADDRESS_CLAMP,
+        AddressW: D3D11_TEXTURE_ADDRESS_CLAMP,
+        ..Default::default()
+    };
+    let mut sampler = None;
+    unsafe { dasc.dev().CreateSamplerState(&desc, Some(&mut sampler)) }
+        .map_err(|e| format!("CreateSamplerState: {e}"))?;
+    sampler.ok_or_else(|| "CreateSamplerState returned null".to_string())
+}
+
+struct ShaderPreset {
+    source: PathBuf,
+    scale_x: f32,
+    scale_y: f32,
+}
+
+struct PresetConfig {
+    shaders: Vec<ShaderPreset>,
+}
+
+impl PresetConfig {
+    /// Minimal `.slangp` parser: reads the `shaders` count plus each
+    /// `shaderN` / `scale_xN` / `scale_yN` key, resolving shader paths
+    /// relative to the preset file's directory.
+    fn parse(preset_path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(preset_path)
+            .map_err(|e| format!("reading preset {}: {e}", preset_path.display()))?;
+        let base_dir = preset_path.parent().unwrap_or_else(|| Path::new("."));
+
+        let mut entries = HashMap::new();
+        for line in contents.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if let Some((key, value)) = line.split_once('=') {
+                entries.insert(key.trim().to_string(), value.trim().trim_matches('"').to_string());
+            }
+        }
+
+        let count: usize = entries
+            .get("shaders")
+            .ok_or("missing `shaders` key in preset")?
+            .parse()
+            .map_err(|_| "`shaders` key is not a number".to_string())?;
+
+        let mut shaders = Vec::with_capacity(count);
+        for i in 0..count {
+            let source = entries
+                .get(&format!("shader{i}"))
+                .ok_or_else(|| format!("missing shader{i} key in preset"))?;
+            let scale_x = entries.get(&format!("scale_x{i}")).and_then(|v| v.parse().ok()).unwrap_or(1.0);
+            let scale_y = entries.get(&format!("scale_y{i}")).and_then(|v| v.parse().ok()).unwrap_or(1.0);
+            shaders.push(ShaderPreset { source: base_dir.join(source), scale_x, scale_y });
+        }
+
+        Ok(PresetConfig { shaders })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `contents` to a temp file whose name is built from `name`
+    /// under the system temp dir and returns its path; callers rely on the path being unique
+    /// per test so parallel test runs don't clobber each other's files.
+    fn write_preset(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("hudhook-filter-chain-test-{name}.slangp"));
+        std::fs::write(&path, contents).expect("write temp preset");
+        path
+    }
+
+    #[test]
+    fn parses_single_pass_preset() {
+        let path = write_preset(
+            "single-pass",
+            r#"
+            shaders = "1"
+            shader0 = "crt.slang"
+            scale_x0 = "2.0"
+            scale_y0 = "2.0"
+            "#,
+        );
+
+        let preset = PresetConfig::parse(&path).expect("parse");
+        assert_eq!(preset.shaders.len(), 1);
+        assert_eq!(preset.shaders[0].source, path.parent().unwrap().join("crt.slang"));
+        assert_eq!(preset.shaders[0].scale_x, 2.0);
+        assert_eq!(preset.shaders[0].scale_y, 2.0);
+    }
+
+    #[test]
+    fn defaults_missing_scale_to_one() {
+        let path = write_preset(
+            "default-scale",
+            r#"
+            shaders = "1"
+            shader0 = "scanlines.slang"
+            "#,
+        );
+
+        let preset = PresetConfig::parse(&path).expect("parse");
+        assert_eq!(preset.shaders[0].scale_x, 1.0);
+        assert_eq!(preset.shaders[0].scale_y, 1.0);
+    }
+
+    #[test]
+    fn rejects_missing_shaders_key() {
+        let path = write_preset("missing-shaders-key", "shader0 = \"crt.slang\"\n");
+
+        let err = PresetConfig::parse(&path).unwrap_err();
+        assert!(err.contains("missing `shaders` key"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn rejects_non_numeric_shaders_key() {
+        let path = write_preset("non-numeric-shaders-key", "shaders = \"two\"\n");
+
+        let err = PresetConfig::parse(&path).unwrap_err();
+        assert!(err.contains("`shaders` key is not a number"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn rejects_missing_shader_entry() {
+        let path = write_preset("missing-shader-entry", "shaders = \"2\"\nshader0 = \"crt.slang\"\n");
+
+        let err = PresetConfig::parse(&path).unwrap_err();
+        assert!(err.contains("missing shader1 key"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn rejects_unreadable_preset() {
+        let path = std::env::temp_dir().join("hudhook-filter-chain-test-does-not-exist.slangp");
+        let _ = std::fs::remove_file(&path);
+
+        let err = PresetConfig::parse(&path).unwrap_err();
+        assert!(err.contains("reading preset"), "unexpected error: {err}");
+    }
+}
@@ -1,24 +1,121 @@
 use imgui::internal::RawWrapper;
-use imgui::{DrawCmd, DrawVert};
+use imgui::{DrawCmd, DrawData, DrawVert, TextureId};
 use log::trace;
-use windows::Win32::Foundation::{HWND, RECT};
+use rustc_hash::FxHashMap;
+use windows::Win32::Foundation::{BOOL, HWND, RECT};
 use windows::Win32::Graphics::Direct3D::D3D11_PRIMITIVE_TOPOLOGY_TRIANGLELIST;
-use windows::Win32::Graphics::Direct3D11::{ID3D11Device, ID3D11DeviceContext};
+use windows::Win32::Graphics::Direct3D11::{
+    ID3D11CommandList, ID3D11Device, ID3D11DeviceContext, ID3D11ShaderResourceView,
+};
 use windows::Win32::Graphics::Dxgi::Common::{DXGI_FORMAT_R16_UINT, DXGI_FORMAT_R32_UINT};
-use windows::Win32::Graphics::Dxgi::IDXGISwapChain;
+use windows::Win32::Graphics::Dxgi::{
+    IDXGIFactory5, IDXGISwapChain, DXGI_FEATURE_PRESENT_ALLOW_TEARING, DXGI_PRESENT_ALLOW_TEARING,
+    DXGI_SWAP_EFFECT_FLIP_DISCARD,
+};
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
 
 use crate::buffers::Buffers;
 use crate::device_and_swapchain::*;
+use crate::filter_chain::FilterChain;
 use crate::shader_program::ShaderProgram;
 use crate::state_backup::StateBackup;
 use crate::texture::Texture;
 
+/// Backend-agnostic surface shared by every `RenderEngine*` implementation.
+///
+/// Each graphics API (D3D11, D3D12, ...) owns its own device/context/swap-chain
+/// types, so those are threaded through as associated types rather than fixed
+/// to a single API. The hook picks the concrete implementation once it knows
+/// which `Present` vtable it intercepted, and drives it purely through this
+/// trait from then on.
+pub trait RenderEngineBackend: Sized {
+    type Device;
+    type DeviceContext;
+    type SwapChain;
+
+    fn new_with_ptrs(dev: Self::Device, dev_ctx: Self::DeviceContext, swap_chain: Self::SwapChain) -> Self;
+
+    fn ctx(&mut self) -> &mut imgui::Context;
+
+    fn render<F: FnOnce(&mut imgui::Ui)>(&mut self, f: F) -> Result<(), String>;
+
+    fn present(&self);
+}
+
+/// Reserved `TextureId` for the font atlas, matching the id imgui assigns
+/// its own default texture.
+const FONT_TEXTURE_ID: usize = 0;
+
+/// Controls the `SyncInterval` / tearing flags `present` submits to DXGI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresentMode {
+    /// `SyncInterval = 1`: present on every vblank.
+    Vsync,
+    /// `SyncInterval = 2`: present on every other vblank.
+    VsyncHalf,
+    /// `SyncInterval = 0`, plus `DXGI_PRESENT_ALLOW_TEARING` when the swap
+    /// chain was created flip-model and the adapter supports tearing.
+    Immediate,
+}
+
+impl Default for PresentMode {
+    fn default() -> Self {
+        PresentMode::Vsync
+    }
+}
+
+/// Checks `DXGI_FEATURE_PRESENT_ALLOW_TEARING` once for the factory that
+/// owns `swap_chain`, so `present` can honor [`PresentMode::Immediate`]
+/// without stalling frames that should run uncapped.
+///
+/// `DXGI_PRESENT_ALLOW_TEARING` is only a valid flag for flip-model swap
+/// chains, so this also requires `SwapEffect == DXGI_SWAP_EFFECT_FLIP_DISCARD`;
+/// passing it to `Present` on a legacy BitBlt/DISCARD swap chain (which
+/// hudhook did not create and cannot assume away) fails with
+/// `DXGI_ERROR_INVALID_CALL` on every frame.
+fn supports_tearing(swap_chain: &IDXGISwapChain) -> bool {
+    let Ok(desc) = (unsafe { swap_chain.GetDesc() }) else {
+        return false;
+    };
+    if desc.SwapEffect != DXGI_SWAP_EFFECT_FLIP_DISCARD {
+        return false;
+    }
+
+    let Ok(factory) = (unsafe { swap_chain.GetParent::<IDXGIFactory5>() }) else {
+        return false;
+    };
+    let mut allow_tearing = BOOL(0);
+    unsafe {
+        factory
+            .CheckFeatureSupport(
+                DXGI_FEATURE_PRESENT_ALLOW_TEARING,
+                &mut allow_tearing as *mut _ as *mut _,
+                std::mem::size_of_val(&allow_tearing) as u32,
+            )
+            .is_ok()
+            && allow_tearing.as_bool()
+    }
+}
+
 pub struct RenderEngine {
     ctx: imgui::Context,
     dasc: DeviceAndSwapChain,
     shader_program: ShaderProgram,
     buffers: Buffers,
     texture: Texture,
+    textures: FxHashMap<usize, ID3D11ShaderResourceView>,
+    next_texture_id: usize,
+    filter_chain: Option<FilterChain>,
+    frame_count: u32,
+    command_list_caching: bool,
+    deferred_ctx: Option<ID3D11DeviceContext>,
+    cached_command_list: Option<(ID3D11CommandList, u64)>,
+    dirty: bool,
+    present_mode: PresentMode,
+    allow_tearing: bool,
 }
 
 impl RenderEngine {
@@ -28,10 +125,164 @@ impl RenderEngine {
         let shader_program = ShaderProgram::new(&dasc).expect("ShaderProgram");
         let buffers = Buffers::new(&dasc);
         let texture = Texture::new(&dasc, &mut ctx.fonts()).expect("Texture");
-        RenderEngine { ctx, dasc, shader_program, buffers, texture }
+        let allow_tearing = supports_tearing(&dasc.swap_chain());
+        RenderEngine {
+            ctx,
+            dasc,
+            shader_program,
+            buffers,
+            texture,
+            textures: FxHashMap::default(),
+            next_texture_id: FONT_TEXTURE_ID + 1,
+            filter_chain: None,
+            frame_count: 0,
+            command_list_caching: false,
+            deferred_ctx: None,
+            cached_command_list: None,
+            dirty: true,
+            present_mode: PresentMode::default(),
+            allow_tearing,
+        }
+    }
+
+    pub fn dev(&self) -> ID3D11Device {
+        self.dasc.dev()
+    }
+
+    /// Always the immediate context, even while
+    /// [`RenderEngine::set_command_list_caching`] is recording draw calls
+    /// onto a deferred context. A `RawCallback` that fetches "the current"
+    /// context via this getter to touch GPU state directly will silently
+    /// miss the command list being built and run against live state
+    /// instead; command-list caching is only safe with callbacks that don't
+    /// rely on this getter reflecting the active recording context.
+    pub fn dev_ctx(&self) -> ID3D11DeviceContext {
+        self.dasc.dev_ctx()
+    }
+
+    pub fn swap_chain(&self) -> IDXGISwapChain {
+        self.dasc.swap_chain()
+    }
+
+    /// Registers `srv` so draw commands that reference the returned
+    /// `TextureId` (e.g. via `Ui::image`) resolve to it instead of the
+    /// font atlas.
+    pub fn register_texture(&mut self, srv: ID3D11ShaderResourceView) -> TextureId {
+        let id = self.next_texture_id;
+        self.next_texture_id += 1;
+        self.textures.insert(id, srv);
+        TextureId::from(id)
     }
 
-    pub fn new_with_ptrs(
+    pub fn unregister_texture(&mut self, id: TextureId) {
+        self.textures.remove(&id.id());
+    }
+
+    /// Loads a RetroArch-style `.slangp` preset and runs it as a
+    /// post-processing pass over every subsequent frame. Replaces any
+    /// previously loaded preset.
+    pub fn load_filter_chain(&mut self, preset_path: &Path) -> Result<(), String> {
+        let size = self.dasc.get_window_rect().map_or((0, 0), |rect| {
+            ((rect.right - rect.left) as u32, (rect.bottom - rect.top) as u32)
+        });
+        self.filter_chain = Some(FilterChain::load(&self.dasc, preset_path, size)?);
+        Ok(())
+    }
+
+    /// Disables post-processing; `render` goes back to presenting the HUD
+    /// straight to the swap-chain's render target.
+    pub fn clear_filter_chain(&mut self) {
+        self.filter_chain = None;
+    }
+
+    /// Opts into recording draw commands once into a deferred-context
+    /// `ID3D11CommandList` and replaying it with `ExecuteCommandList` on
+    /// subsequent frames, instead of re-issuing every draw call on the
+    /// immediate context each frame. Disabling it drops the cached list.
+    pub fn set_command_list_caching(&mut self, enabled: bool) {
+        self.command_list_caching = enabled;
+        if !enabled {
+            self.cached_command_list = None;
+        }
+    }
+
+    /// Forces the next frame to re-record its command list even if the
+    /// draw data layout hasn't changed, e.g. after the caller knows the UI
+    /// content (not just its shape) is now stale.
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Sets the `SyncInterval` / tearing behavior used by `present`.
+    /// [`PresentMode::Immediate`] always presents with `SyncInterval = 0`;
+    /// it additionally allows tearing only if the swap chain is flip-model
+    /// and its adapter supports tearing, and otherwise presents without
+    /// tearing.
+    pub fn set_present_mode(&mut self, mode: PresentMode) {
+        self.present_mode = mode;
+    }
+
+    fn deferred_ctx(&mut self) -> ID3D11DeviceContext {
+        if self.deferred_ctx.is_none() {
+            let mut ctx = None;
+            unsafe { self.dasc.dev().CreateDeferredContext(0, &mut ctx) }.expect("CreateDeferredContext");
+            self.deferred_ctx = ctx;
+        }
+        self.deferred_ctx.clone().expect("deferred context")
+    }
+}
+
+/// Hashes the actual vertex and index bytes of every draw list, plus the
+/// sequence of draw commands (their kind, element count, clip rect, and
+/// bound texture).
+///
+/// Earlier versions of this only hashed vertex/index *counts* and command
+/// kind/count/texture-id, so a HUD element with a changing displayed value
+/// but an unchanged glyph/quad count (an FPS counter, a clock, a health
+/// readout) would silently keep replaying stale cached geometry forever
+/// once [`RenderEngine::set_command_list_caching`] was on, since the
+/// signature never changed. Hashing the vertex/index bytes themselves
+/// closes that hole at the cost of making cache hits exactly as expensive
+/// to detect as just re-recording would be for small HUDs; callers with
+/// very large, rarely-changing overlays may still prefer
+/// [`RenderEngine::invalidate`] plus a cheaper custom signature.
+fn draw_data_layout_signature(draw_data: &DrawData) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    draw_data.total_vtx_count.hash(&mut hasher);
+    draw_data.total_idx_count.hash(&mut hasher);
+    for cl in draw_data.draw_lists() {
+        hash_bytes(cl.vtx_buffer(), &mut hasher);
+        cl.idx_buffer().hash(&mut hasher);
+        for cmd in cl.commands() {
+            match cmd {
+                DrawCmd::Elements { count, cmd_params } => {
+                    0u8.hash(&mut hasher);
+                    count.hash(&mut hasher);
+                    cmd_params.clip_rect.map(|v| v.to_bits()).hash(&mut hasher);
+                    cmd_params.texture_id.id().hash(&mut hasher);
+                },
+                DrawCmd::ResetRenderState => 1u8.hash(&mut hasher),
+                DrawCmd::RawCallback { .. } => 2u8.hash(&mut hasher),
+            }
+        }
+    }
+    hasher.finish()
+}
+
+/// Hashes `values` by its raw bytes rather than field-by-field, since
+/// `DrawVert` contains `f32`s and doesn't implement `Hash`.
+fn hash_bytes<T: Copy>(values: &[T], hasher: &mut impl Hasher) {
+    let bytes =
+        unsafe { std::slice::from_raw_parts(values.as_ptr() as *const u8, std::mem::size_of_val(values)) };
+    bytes.hash(hasher);
+}
+
+impl RenderEngineBackend for RenderEngine {
+    type Device = ID3D11Device;
+    type DeviceContext = ID3D11DeviceContext;
+    type SwapChain = IDXGISwapChain;
+
+    fn new_with_ptrs(
         dev: ID3D11Device,
         dev_ctx: ID3D11DeviceContext,
         swap_chain: IDXGISwapChain,
@@ -41,26 +292,31 @@ impl RenderEngine {
         let shader_program = ShaderProgram::new(&dasc).expect("ShaderProgram");
         let buffers = Buffers::new(&dasc);
         let texture = Texture::new(&dasc, &mut ctx.fonts()).expect("Texture");
-        RenderEngine { ctx, dasc, shader_program, buffers, texture }
+        let allow_tearing = supports_tearing(&dasc.swap_chain());
+        RenderEngine {
+            ctx,
+            dasc,
+            shader_program,
+            buffers,
+            texture,
+            textures: FxHashMap::default(),
+            next_texture_id: FONT_TEXTURE_ID + 1,
+            filter_chain: None,
+            frame_count: 0,
+            command_list_caching: false,
+            deferred_ctx: None,
+            cached_command_list: None,
+            dirty: true,
+            present_mode: PresentMode::default(),
+            allow_tearing,
+        }
     }
 
-    pub fn ctx(&mut self) -> &mut imgui::Context {
+    fn ctx(&mut self) -> &mut imgui::Context {
         &mut self.ctx
     }
 
-    pub fn dev(&self) -> ID3D11Device {
-        self.dasc.dev()
-    }
-
-    pub fn dev_ctx(&self) -> ID3D11DeviceContext {
-        self.dasc.dev_ctx()
-    }
-
-    pub fn swap_chain(&self) -> IDXGISwapChain {
-        self.dasc.swap_chain()
-    }
-
-    pub fn render<F: FnOnce(&mut imgui::Ui)>(&mut self, f: F) -> Result<(), String> {
+    fn render<F: FnOnce(&mut imgui::Ui)>(&mut self, f: F) -> Result<(), String> {
         trace!("Rendering started");
         let state_backup = StateBackup::backup(self.dasc.dev_ctx());
 
@@ -71,11 +327,11 @@ impl RenderEngine {
             rect.bottom -= rect.top;
             rect.top = 0;
             rect.left = 0;
-            self.dasc.set_viewport(rect);
-            self.dasc.set_render_target();
+            self.dasc.set_viewport(&self.dasc.dev_ctx(), rect);
+            self.dasc.set_render_target(&self.dasc.dev_ctx());
         }
         trace!("Set shader program state");
-        unsafe { self.shader_program.set_state(&self.dasc) };
+        unsafe { self.shader_program.set_state(&self.dasc.dev_ctx()) };
 
         let mut ui = self.ctx.frame();
         f(&mut ui);
@@ -88,75 +344,107 @@ impl RenderEngine {
             return Err(format!("Insufficient display size {} x {}", width, height));
         }
 
-        unsafe {
-            let dev_ctx = self.dasc.dev_ctx();
-
-            trace!("Setting up buffers");
-            self.buffers.set_constant_buffer(&self.dasc, [x, y, x + width, y + height]);
-            self.buffers.set_buffers(&self.dasc, draw_data.draw_lists());
-
-            dev_ctx.IASetVertexBuffers(
-                0,
-                1,
-                &Some(self.buffers.vtx_buffer()),
-                &(std::mem::size_of::<DrawVert>() as u32),
-                &0,
-            );
-            dev_ctx.IASetIndexBuffer(
-                self.buffers.idx_buffer(),
-                if std::mem::size_of::<imgui::DrawIdx>() == 2 {
-                    DXGI_FORMAT_R16_UINT
-                } else {
-                    DXGI_FORMAT_R32_UINT
-                },
-                0,
-            );
-            dev_ctx.IASetPrimitiveTopology(D3D11_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
-            dev_ctx.VSSetConstantBuffers(0, &[Some(self.buffers.mtx_buffer())]);
-            dev_ctx.PSSetShaderResources(0, &[Some(self.texture.tex_view())]);
-
-            let mut vtx_offset = 0usize;
-            let mut idx_offset = 0usize;
-
-            trace!("Rendering draw lists");
-            for cl in draw_data.draw_lists() {
-                for cmd in cl.commands() {
-                    match cmd {
-                        DrawCmd::Elements { count, cmd_params } => {
-                            trace!("Rendering {count} elements");
-                            let [cx, cy, cw, ch] = cmd_params.clip_rect;
-                            dev_ctx.RSSetScissorRects(&[RECT {
-                                left: (cx - x) as i32,
-                                top: (cy - y) as i32,
-                                right: (cw - x) as i32,
-                                bottom: (ch - y) as i32,
-                            }]);
-
-                            // let srv = cmd_params.texture_id.id();
-                            // We only load the font texture. This may not be correct.
-                            self.dasc.set_shader_resources(self.texture.tex_view());
-
-                            trace!("Drawing indexed {count}, {idx_offset}, {vtx_offset}");
-                            dev_ctx.DrawIndexed(count as u32, idx_offset as _, vtx_offset as _);
-
-                            idx_offset += count;
-                        },
-                        DrawCmd::ResetRenderState => {
-                            trace!("Resetting render state");
-                            self.dasc.setup_state(draw_data);
-                            self.shader_program.set_state(&self.dasc);
-                        },
-                        DrawCmd::RawCallback { callback, raw_cmd } => {
-                            trace!("Executing raw callback");
-                            callback(cl.raw(), raw_cmd)
-                        },
+        let layout_signature = draw_data_layout_signature(draw_data);
+        let cache_hit = self.command_list_caching
+            && !self.dirty
+            && self.cached_command_list.as_ref().is_some_and(|(_, sig)| *sig == layout_signature);
+
+        if cache_hit {
+            trace!("Replaying cached command list");
+            unsafe {
+                self.dasc.dev_ctx().ExecuteCommandList(&self.cached_command_list.as_ref().unwrap().0, false)
+            };
+        } else {
+            let recording = self.command_list_caching;
+            let dev_ctx = if recording { self.deferred_ctx() } else { self.dasc.dev_ctx() };
+
+            unsafe {
+                trace!("Setting up buffers");
+                self.buffers.set_constant_buffer(&self.dasc, [x, y, x + width, y + height]);
+                self.buffers.set_buffers(&self.dasc, draw_data.draw_lists());
+
+                dev_ctx.IASetVertexBuffers(
+                    0,
+                    1,
+                    &Some(self.buffers.vtx_buffer()),
+                    &(std::mem::size_of::<DrawVert>() as u32),
+                    &0,
+                );
+                dev_ctx.IASetIndexBuffer(
+                    self.buffers.idx_buffer(),
+                    if std::mem::size_of::<imgui::DrawIdx>() == 2 {
+                        DXGI_FORMAT_R16_UINT
+                    } else {
+                        DXGI_FORMAT_R32_UINT
+                    },
+                    0,
+                );
+                dev_ctx.IASetPrimitiveTopology(D3D11_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+                dev_ctx.VSSetConstantBuffers(0, &[Some(self.buffers.mtx_buffer())]);
+                dev_ctx.PSSetShaderResources(0, &[Some(self.texture.tex_view())]);
+
+                let mut vtx_offset = 0usize;
+                let mut idx_offset = 0usize;
+
+                trace!("Rendering draw lists");
+                for cl in draw_data.draw_lists() {
+                    for cmd in cl.commands() {
+                        match cmd {
+                            DrawCmd::Elements { count, cmd_params } => {
+                                trace!("Rendering {count} elements");
+                                let [cx, cy, cw, ch] = cmd_params.clip_rect;
+                                dev_ctx.RSSetScissorRects(&[RECT {
+                                    left: (cx - x) as i32,
+                                    top: (cy - y) as i32,
+                                    right: (cw - x) as i32,
+                                    bottom: (ch - y) as i32,
+                                }]);
+
+                                let srv = self
+                                    .textures
+                                    .get(&cmd_params.texture_id.id())
+                                    .cloned()
+                                    .unwrap_or_else(|| self.texture.tex_view());
+                                dev_ctx.PSSetShaderResources(0, &[Some(srv)]);
+
+                                trace!("Drawing indexed {count}, {idx_offset}, {vtx_offset}");
+                                dev_ctx.DrawIndexed(count as u32, idx_offset as _, vtx_offset as _);
+
+                                idx_offset += count;
+                            },
+                            DrawCmd::ResetRenderState => {
+                                trace!("Resetting render state");
+                                self.dasc.setup_state(&dev_ctx, draw_data);
+                                self.shader_program.set_state(&dev_ctx);
+                            },
+                            DrawCmd::RawCallback { callback, raw_cmd } => {
+                                trace!("Executing raw callback");
+                                callback(cl.raw(), raw_cmd)
+                            },
+                        }
                     }
+                    vtx_offset += cl.vtx_buffer().len();
+                }
+
+                // self.dasc.swap_chain().Present(1, 0);
+
+                if recording {
+                    trace!("Recording command list for reuse");
+                    let command_list =
+                        dev_ctx.FinishCommandList(false).expect("FinishCommandList");
+                    self.dasc.dev_ctx().ExecuteCommandList(&command_list, false);
+                    self.cached_command_list = Some((command_list, layout_signature));
                 }
-                vtx_offset += cl.vtx_buffer().len();
             }
+        }
+        self.dirty = false;
 
-            // self.dasc.swap_chain().Present(1, 0);
+        if let Some(filter_chain) = self.filter_chain.as_mut() {
+            trace!("Running filter chain");
+            let output_size = (width as u32, height as u32);
+            filter_chain.render(&self.dasc, self.dasc.back_buffer_srv(), output_size, self.frame_count)?;
         }
+        self.frame_count = self.frame_count.wrapping_add(1);
 
         trace!("Restoring state backup");
         state_backup.restore(self.dasc.dev_ctx());
@@ -166,8 +454,15 @@ impl RenderEngine {
         Ok(())
     }
 
-    pub fn present(&self) {
-        if let Err(e) = unsafe { self.dasc.swap_chain().Present(1, 0) } {
+    fn present(&self) {
+        let (sync_interval, flags) = match self.present_mode {
+            PresentMode::Vsync => (1, 0),
+            PresentMode::VsyncHalf => (2, 0),
+            PresentMode::Immediate if self.allow_tearing => (0, DXGI_PRESENT_ALLOW_TEARING),
+            PresentMode::Immediate => (0, 0),
+        };
+
+        if let Err(e) = unsafe { self.dasc.swap_chain().Present(sync_interval, flags) } {
             log::error!("Present: {e}");
         }
     }
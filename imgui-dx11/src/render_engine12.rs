@@ -0,0 +1,202 @@
+use imgui::{DrawCmd, TextureId};
+use log::trace;
+use rustc_hash::FxHashMap;
+use windows::Win32::Graphics::Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST;
+use windows::Win32::Graphics::Direct3D12::{ID3D12CommandQueue, ID3D12Device, ID3D12Resource};
+use windows::Win32::Graphics::Dxgi::{IDXGISwapChain3, DXGI_PRESENT_ALLOW_TEARING};
+use windows::Win32::Foundation::RECT;
+
+use crate::buffers12::Buffers12;
+use crate::device_and_swapchain12::DeviceAndSwapChain12;
+use crate::render_engine::{PresentMode, RenderEngineBackend};
+use crate::shader_program12::ShaderProgram12;
+use crate::texture12::Texture12;
+
+/// Maximum number of distinct `ID3D12Resource` SRVs this engine's descriptor
+/// heap can hold, including the font atlas in slot 0.
+const MAX_TEXTURES: u32 = 256;
+
+/// Descriptor-heap slot permanently reserved for the font atlas, matching
+/// `Texture12::new`'s slot 0. Used as the fallback for `TextureId`s that
+/// were never registered (or were already unregistered), the same way
+/// `RenderEngine::render` falls back to `self.texture.tex_view()`.
+const FONT_TEXTURE_SLOT: u32 = 0;
+
+/// D3D12 implementation of [`RenderEngineBackend`], selected by the hook
+/// when it intercepts `IDXGISwapChain3::Present` on a D3D12 device instead
+/// of the D3D11 `IDXGISwapChain::Present` the base `RenderEngine` handles.
+pub struct RenderEngine12 {
+    ctx: imgui::Context,
+    dasc: DeviceAndSwapChain12,
+    shader_program: ShaderProgram12,
+    buffers: Buffers12,
+    texture: Texture12,
+    textures: FxHashMap<usize, u32>,
+    next_texture_id: usize,
+    present_mode: PresentMode,
+}
+
+impl RenderEngine12 {
+    pub fn dev(&self) -> ID3D12Device {
+        self.dasc.dev()
+    }
+
+    pub fn command_queue(&self) -> ID3D12CommandQueue {
+        self.dasc.command_queue()
+    }
+
+    pub fn swap_chain(&self) -> IDXGISwapChain3 {
+        self.dasc.swap_chain()
+    }
+
+    /// Sets the `SyncInterval` / tearing behavior used by `present`. See
+    /// `RenderEngine::set_present_mode` for the D3D11 equivalent.
+    pub fn set_present_mode(&mut self, mode: PresentMode) {
+        self.present_mode = mode;
+    }
+
+    /// Registers `resource` so draw commands that reference the returned
+    /// `TextureId` (e.g. via `Ui::image`) resolve to it instead of the font
+    /// atlas. See `RenderEngine::register_texture` for the D3D11 equivalent.
+    pub fn register_texture(&mut self, resource: &ID3D12Resource) -> Result<TextureId, String> {
+        let slot = self.texture.allocate_slot(resource).map_err(|e| format!("allocate_slot: {e}"))?;
+        let id = self.next_texture_id;
+        self.next_texture_id += 1;
+        self.textures.insert(id, slot);
+        Ok(TextureId::from(id))
+    }
+
+    pub fn unregister_texture(&mut self, id: TextureId) {
+        self.textures.remove(&id.id());
+    }
+}
+
+impl RenderEngineBackend for RenderEngine12 {
+    type Device = ID3D12Device;
+    type DeviceContext = ID3D12CommandQueue;
+    type SwapChain = IDXGISwapChain3;
+
+    fn new_with_ptrs(dev: ID3D12Device, command_queue: ID3D12CommandQueue, swap_chain: IDXGISwapChain3) -> Self {
+        let mut ctx = imgui::Context::create();
+        let dasc = DeviceAndSwapChain12::new_with_ptrs(dev, command_queue, swap_chain);
+        let shader_program = ShaderProgram12::new(&dasc.dev(), dasc.rtv_format()).expect("ShaderProgram12");
+        let buffers = Buffers12::new(&dasc.dev(), dasc.buffer_count());
+        let texture = Texture12::new(&dasc.dev(), ctx.fonts(), MAX_TEXTURES).expect("Texture12");
+        RenderEngine12 {
+            ctx,
+            dasc,
+            shader_program,
+            buffers,
+            texture,
+            textures: FxHashMap::default(),
+            next_texture_id: FONT_TEXTURE_SLOT as usize + 1,
+            present_mode: PresentMode::default(),
+        }
+    }
+
+    fn ctx(&mut self) -> &mut imgui::Context {
+        &mut self.ctx
+    }
+
+    fn render<F: FnOnce(&mut imgui::Ui)>(&mut self, f: F) -> Result<(), String> {
+        trace!("Rendering started (D3D12)");
+        let frame_index = self.dasc.frame_index();
+
+        if let Some(rect) = self.dasc.get_window_rect() {
+            self.ctx.io_mut().display_size = [rect.right as f32, rect.bottom as f32];
+        }
+
+        let mut ui = self.ctx.frame();
+        f(&mut ui);
+        let draw_data = ui.render();
+
+        let [x, y] = draw_data.display_pos;
+        let [width, height] = draw_data.display_size;
+        if width <= 0. && height <= 0. {
+            return Err(format!("Insufficient display size {} x {}", width, height));
+        }
+
+        self.dasc.begin_frame(frame_index, (width, height));
+
+        let dev = self.dasc.dev();
+        self.buffers.set_constant_buffer(frame_index, [x, y, x + width, y + height]);
+        self.buffers.set_buffers(&dev, frame_index, draw_data.draw_lists());
+
+        let command_list = self.dasc.command_list();
+        unsafe {
+            self.shader_program.set_state(&command_list);
+            command_list.SetDescriptorHeaps(&[Some(self.texture.heap())]);
+            command_list.IASetPrimitiveTopology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+            command_list.IASetVertexBuffers(0, Some(&[self.buffers.vertex_buffer_view(frame_index)]));
+            command_list.IASetIndexBuffer(Some(&self.buffers.index_buffer_view(frame_index)));
+            command_list
+                .SetGraphicsRootConstantBufferView(0, self.buffers.constant_buffer_address(frame_index));
+
+            let mut vtx_offset = 0usize;
+            let mut idx_offset = 0usize;
+
+            trace!("Rendering draw lists");
+            for cl in draw_data.draw_lists() {
+                for cmd in cl.commands() {
+                    match cmd {
+                        DrawCmd::Elements { count, cmd_params } => {
+                            trace!("Rendering {count} elements");
+                            let [cx, cy, cw, ch] = cmd_params.clip_rect;
+                            command_list.RSSetScissorRects(&[RECT {
+                                left: (cx - x) as i32,
+                                top: (cy - y) as i32,
+                                right: (cw - x) as i32,
+                                bottom: (ch - y) as i32,
+                            }]);
+
+                            let slot = self
+                                .textures
+                                .get(&cmd_params.texture_id.id())
+                                .copied()
+                                .unwrap_or(FONT_TEXTURE_SLOT);
+                            command_list.SetGraphicsRootDescriptorTable(1, self.texture.gpu_handle(slot));
+
+                            trace!("Drawing indexed {count}, {idx_offset}, {vtx_offset}");
+                            command_list.DrawIndexedInstanced(
+                                count as u32,
+                                1,
+                                idx_offset as u32,
+                                vtx_offset as i32,
+                                0,
+                            );
+
+                            idx_offset += count;
+                        },
+                        DrawCmd::ResetRenderState => {
+                            trace!("Resetting render state");
+                            self.shader_program.set_state(&command_list);
+                        },
+                        DrawCmd::RawCallback { callback, raw_cmd } => {
+                            trace!("Executing raw callback");
+                            callback(cl.raw(), raw_cmd)
+                        },
+                    }
+                }
+                vtx_offset += cl.vtx_buffer().len();
+            }
+        }
+
+        self.dasc.end_frame(frame_index);
+
+        trace!("Rendering done (D3D12)");
+        Ok(())
+    }
+
+    fn present(&self) {
+        let (sync_interval, flags) = match self.present_mode {
+            PresentMode::Vsync => (1, 0),
+            PresentMode::VsyncHalf => (2, 0),
+            PresentMode::Immediate if self.dasc.allow_tearing() => (0, DXGI_PRESENT_ALLOW_TEARING),
+            PresentMode::Immediate => (0, 0),
+        };
+
+        if let Err(e) = unsafe { self.dasc.swap_chain().Present(sync_interval, flags) } {
+            log::error!("Present: {e}");
+        }
+    }
+}
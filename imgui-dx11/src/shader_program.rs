@@ -0,0 +1,176 @@
+use windows::core::s;
+use windows::Win32::Graphics::Direct3D::ID3DBlob;
+use windows::Win32::Graphics::Direct3D11::*;
+use windows::Win32::Graphics::Dxgi::Common::{DXGI_FORMAT_R32G32_FLOAT, DXGI_FORMAT_R8G8B8A8_UNORM};
+
+use crate::device_and_swapchain::DeviceAndSwapChain;
+
+/// D3D11 has no baked pipeline state object: the vertex/pixel shaders,
+/// input layout, blend state and sampler are each their own free-standing
+/// COM object, bound individually by `set_state` instead of a single
+/// `SetPipelineState` call.
+pub struct ShaderProgram {
+    input_layout: ID3D11InputLayout,
+    vertex_shader: ID3D11VertexShader,
+    pixel_shader: ID3D11PixelShader,
+    blend_state: ID3D11BlendState,
+    sampler: ID3D11SamplerState,
+}
+
+impl ShaderProgram {
+    /// Compiles the built-in shader used for the main imgui HUD quad.
+    pub fn new(dasc: &DeviceAndSwapChain) -> Result<Self, String> {
+        Self::compile(dasc, VERTEX_SHADER_SRC, PIXEL_SHADER_SRC)
+    }
+
+    /// Compiles a `FilterChain` pass's shader source, read from `source`
+    /// and compiled as HLSL with the same `vs_main`/`ps_main` entry points
+    /// the built-in shader uses.
+    pub fn new_from_source(dasc: &DeviceAndSwapChain, source: &std::path::Path) -> Result<Self, String> {
+        let src = std::fs::read_to_string(source).map_err(|e| format!("reading {}: {e}", source.display()))?;
+        Self::compile(dasc, &src, &src)
+    }
+
+    fn compile(dasc: &DeviceAndSwapChain, vs_src: &str, ps_src: &str) -> Result<Self, String> {
+        let vs_blob = compile_shader(vs_src, s!("vs_main"), s!("vs_5_0"))?;
+        let ps_blob = compile_shader(ps_src, s!("ps_main"), s!("ps_5_0"))?;
+
+        let mut vertex_shader = None;
+        unsafe { dasc.dev().CreateVertexShader(blob_bytes(&vs_blob), None, Some(&mut vertex_shader)) }
+            .map_err(|e| format!("CreateVertexShader: {e}"))?;
+
+        let mut pixel_shader = None;
+        unsafe { dasc.dev().CreatePixelShader(blob_bytes(&ps_blob), None, Some(&mut pixel_shader)) }
+            .map_err(|e| format!("CreatePixelShader: {e}"))?;
+
+        let input_elements = [
+            input_element(s!("POSITION"), DXGI_FORMAT_R32G32_FLOAT, 0),
+            input_element(s!("TEXCOORD"), DXGI_FORMAT_R32G32_FLOAT, 8),
+            input_element(s!("COLOR"), DXGI_FORMAT_R8G8B8A8_UNORM, 16),
+        ];
+        let mut input_layout = None;
+        unsafe { dasc.dev().CreateInputLayout(&input_elements, blob_bytes(&vs_blob), Some(&mut input_layout)) }
+            .map_err(|e| format!("CreateInputLayout: {e}"))?;
+
+        let blend_state = create_blend_state(dasc)?;
+        let sampler = create_sampler(dasc)?;
+
+        Ok(ShaderProgram {
+            input_layout: input_layout.expect("input layout"),
+            vertex_shader: vertex_shader.expect("vertex shader"),
+            pixel_shader: pixel_shader.expect("pixel shader"),
+            blend_state,
+            sampler,
+        })
+    }
+
+    /// Binds every piece of state this shader program owns on `dev_ctx`,
+    /// which callers pass explicitly (immediate or deferred) rather than
+    /// this always targeting a fixed context.
+    pub unsafe fn set_state(&self, dev_ctx: &ID3D11DeviceContext) {
+        dev_ctx.IASetInputLayout(&self.input_layout);
+        dev_ctx.VSSetShader(&self.vertex_shader, None);
+        dev_ctx.PSSetShader(&self.pixel_shader, None);
+        dev_ctx.OMSetBlendState(&self.blend_state, Some(&[0.; 4]), u32::MAX);
+        dev_ctx.PSSetSamplers(0, &[Some(self.sampler.clone())]);
+    }
+}
+
+fn create_blend_state(dasc: &DeviceAndSwapChain) -> Result<ID3D11BlendState, String> {
+    let mut desc = D3D11_BLEND_DESC::default();
+    desc.RenderTarget[0] = D3D11_RENDER_TARGET_BLEND_DESC {
+        BlendEnable: true.into(),
+        SrcBlend: D3D11_BLEND_SRC_ALPHA,
+        DestBlend: D3D11_BLEND_INV_SRC_ALPHA,
+        BlendOp: D3D11_BLEND_OP_ADD,
+        SrcBlendAlpha: D3D11_BLEND_INV_SRC_ALPHA,
+        DestBlendAlpha: D3D11_BLEND_ZERO,
+        BlendOpAlpha: D3D11_BLEND_OP_ADD,
+        RenderTargetWriteMask: D3D11_COLOR_WRITE_ENABLE_ALL.0 as u8,
+    };
+
+    let mut blend_state = None;
+    unsafe { dasc.dev().CreateBlendState(&desc, Some(&mut blend_state)) }.map_err(|e| format!("CreateBlendState: {e}"))?;
+    blend_state.ok_or_else(|| "CreateBlendState returned null".to_string())
+}
+
+fn create_sampler(dasc: &DeviceAndSwapChain) -> Result<ID3D11SamplerState, String> {
+    let desc = D3D11_SAMPLER_DESC {
+        Filter: D3D11_FILTER_MIN_MAG_MIP_LINEAR,
+        AddressU: D3D11_TEXTURE_ADDRESS_WRAP,
+        AddressV: D3D11_TEXTURE_ADDRESS_WRAP,
+        AddressW: D3D11_TEXTURE_ADDRESS_WRAP,
+        ..Default::default()
+    };
+    let mut sampler = None;
+    unsafe { dasc.dev().CreateSamplerState(&desc, Some(&mut sampler)) }.map_err(|e| format!("CreateSamplerState: {e}"))?;
+    sampler.ok_or_else(|| "CreateSamplerState returned null".to_string())
+}
+
+fn input_element(
+    name: windows::core::PCSTR,
+    format: windows::Win32::Graphics::Dxgi::Common::DXGI_FORMAT,
+    offset: u32,
+) -> D3D11_INPUT_ELEMENT_DESC {
+    D3D11_INPUT_ELEMENT_DESC {
+        SemanticName: name,
+        SemanticIndex: 0,
+        Format: format,
+        InputSlot: 0,
+        AlignedByteOffset: offset,
+        InputSlotClass: D3D11_INPUT_PER_VERTEX_DATA,
+        InstanceDataStepRate: 0,
+    }
+}
+
+fn blob_bytes(blob: &ID3DBlob) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(blob.GetBufferPointer() as *const u8, blob.GetBufferSize()) }
+}
+
+fn compile_shader(src: &str, entry_point: windows::core::PCSTR, target: windows::core::PCSTR) -> Result<ID3DBlob, String> {
+    use windows::Win32::Graphics::Direct3D::Fxc::D3DCompile;
+
+    let mut blob: Option<ID3DBlob> = None;
+    let mut error_blob: Option<ID3DBlob> = None;
+    unsafe {
+        D3DCompile(src.as_ptr() as _, src.len(), None, None, None, entry_point, target, 0, 0, &mut blob, Some(&mut error_blob))
+    }
+    .map_err(|e| format!("D3DCompile: {e}"))?;
+    blob.ok_or_else(|| "D3DCompile produced no blob".to_string())
+}
+
+const VERTEX_SHADER_SRC: &str = r#"
+cbuffer vertexBuffer : register(b0) {
+    float4x4 ProjectionMatrix;
+};
+struct VS_INPUT {
+    float2 pos : POSITION;
+    float2 uv  : TEXCOORD0;
+    float4 col : COLOR0;
+};
+struct PS_INPUT {
+    float4 pos : SV_POSITION;
+    float4 col : COLOR0;
+    float2 uv  : TEXCOORD0;
+};
+PS_INPUT vs_main(VS_INPUT input) {
+    PS_INPUT output;
+    output.pos = mul(ProjectionMatrix, float4(input.pos.xy, 0.f, 1.f));
+    output.col = input.col;
+    output.uv  = input.uv;
+    return output;
+}
+"#;
+
+const PIXEL_SHADER_SRC: &str = r#"
+Texture2D tex0 : register(t0);
+SamplerState sampler0 : register(s0);
+struct PS_INPUT {
+    float4 pos : SV_POSITION;
+    float4 col : COLOR0;
+    float2 uv  : TEXCOORD0;
+};
+float4 ps_main(PS_INPUT input) : SV_Target {
+    return input.col * tex0.Sample(sampler0, input.uv);
+}
+"#;
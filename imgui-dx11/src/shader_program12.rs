@@ -0,0 +1,250 @@
+use windows::Win32::Graphics::Direct3D::ID3DBlob;
+use windows::Win32::Graphics::Direct3D12::*;
+use windows::Win32::Graphics::Dxgi::Common::{
+    DXGI_FORMAT, DXGI_FORMAT_R32G32_FLOAT, DXGI_FORMAT_R8G8B8A8_UNORM, DXGI_SAMPLE_DESC,
+};
+use windows::core::s;
+
+/// D3D12 has no global pipeline state: vertex/pixel shaders, blend/raster
+/// state, and the root signature describing the CBV/SRV/sampler bindings
+/// are all baked together into a single `ID3D12PipelineState`, so this
+/// owns that plus the root signature used to build it.
+pub struct ShaderProgram12 {
+    root_signature: ID3D12RootSignature,
+    pipeline_state: ID3D12PipelineState,
+}
+
+impl ShaderProgram12 {
+    /// `rtv_format` must match the render target the pipeline state will
+    /// actually be drawing into (the swap chain's back-buffer format, or
+    /// a filter pass's intermediate target format) — D3D12 bakes the RTV
+    /// format into the PSO, unlike D3D11's free-standing `OMSetRenderTargets`.
+    pub fn new(dev: &ID3D12Device, rtv_format: DXGI_FORMAT) -> windows::core::Result<Self> {
+        let root_signature = create_root_signature(dev)?;
+        let pipeline_state = create_pipeline_state(dev, &root_signature, rtv_format)?;
+        Ok(ShaderProgram12 { root_signature, pipeline_state })
+    }
+
+    pub fn root_signature(&self) -> ID3D12RootSignature {
+        self.root_signature.clone()
+    }
+
+    pub fn pipeline_state(&self) -> ID3D12PipelineState {
+        self.pipeline_state.clone()
+    }
+
+    /// Binds the root signature and pipeline state on `command_list`, mirroring
+    /// `ShaderProgram::set_state` for the D3D11 backend.
+    pub unsafe fn set_state(&self, command_list: &ID3D12GraphicsCommandList) {
+        command_list.SetGraphicsRootSignature(&self.root_signature);
+        command_list.SetPipelineState(&self.pipeline_state);
+    }
+}
+
+fn create_root_signature(dev: &ID3D12Device) -> windows::core::Result<ID3D12RootSignature> {
+    // Slot 0: vertex-shader CBV (MVP and friends), slot 1: pixel-shader SRV
+    // table (the currently bound texture), plus a static linear-filtering,
+    // wrapping sampler at s0.
+    let descriptor_range = D3D12_DESCRIPTOR_RANGE {
+        RangeType: D3D12_DESCRIPTOR_RANGE_TYPE_SRV,
+        NumDescriptors: 1,
+        BaseShaderRegister: 0,
+        RegisterSpace: 0,
+        OffsetInDescriptorsFromTableStart: D3D12_DESCRIPTOR_RANGE_OFFSET_APPEND,
+    };
+
+    let parameters = [
+        D3D12_ROOT_PARAMETER {
+            ParameterType: D3D12_ROOT_PARAMETER_TYPE_CBV,
+            ShaderVisibility: D3D12_SHADER_VISIBILITY_VERTEX,
+            Anonymous: D3D12_ROOT_PARAMETER_0 {
+                Descriptor: D3D12_ROOT_DESCRIPTOR { ShaderRegister: 0, RegisterSpace: 0 },
+            },
+        },
+        D3D12_ROOT_PARAMETER {
+            ParameterType: D3D12_ROOT_PARAMETER_TYPE_DESCRIPTOR_TABLE,
+            ShaderVisibility: D3D12_SHADER_VISIBILITY_PIXEL,
+            Anonymous: D3D12_ROOT_PARAMETER_0 {
+                DescriptorTable: D3D12_ROOT_DESCRIPTOR_TABLE {
+                    NumDescriptorRanges: 1,
+                    pDescriptorRanges: &descriptor_range,
+                },
+            },
+        },
+    ];
+
+    let sampler = D3D12_STATIC_SAMPLER_DESC {
+        Filter: D3D12_FILTER_MIN_MAG_MIP_LINEAR,
+        AddressU: D3D12_TEXTURE_ADDRESS_MODE_WRAP,
+        AddressV: D3D12_TEXTURE_ADDRESS_MODE_WRAP,
+        AddressW: D3D12_TEXTURE_ADDRESS_MODE_WRAP,
+        ComparisonFunc: D3D12_COMPARISON_FUNC_ALWAYS,
+        ShaderRegister: 0,
+        RegisterSpace: 0,
+        ShaderVisibility: D3D12_SHADER_VISIBILITY_PIXEL,
+        ..Default::default()
+    };
+
+    let desc = D3D12_ROOT_SIGNATURE_DESC {
+        NumParameters: parameters.len() as u32,
+        pParameters: parameters.as_ptr(),
+        NumStaticSamplers: 1,
+        pStaticSamplers: &sampler,
+        Flags: D3D12_ROOT_SIGNATURE_FLAG_ALLOW_INPUT_ASSEMBLER_INPUT_LAYOUT,
+    };
+
+    let mut blob: Option<ID3DBlob> = None;
+    let mut error_blob: Option<ID3DBlob> = None;
+    unsafe {
+        D3D12SerializeRootSignature(&desc, D3D_ROOT_SIGNATURE_VERSION_1, &mut blob, Some(&mut error_blob))?;
+        let blob = blob.expect("root signature blob");
+        dev.CreateRootSignature(
+            0,
+            std::slice::from_raw_parts(blob.GetBufferPointer() as *const u8, blob.GetBufferSize()),
+        )
+    }
+}
+
+fn create_pipeline_state(
+    dev: &ID3D12Device,
+    root_signature: &ID3D12RootSignature,
+    rtv_format: DXGI_FORMAT,
+) -> windows::core::Result<ID3D12PipelineState> {
+    let vs = compile_shader(VERTEX_SHADER_SRC, s!("vs_main"), s!("vs_5_0"))?;
+    let ps = compile_shader(PIXEL_SHADER_SRC, s!("ps_main"), s!("ps_5_0"))?;
+
+    let input_elements = [
+        input_element(s!("POSITION"), 0, DXGI_FORMAT_R32G32_FLOAT),
+        input_element(s!("TEXCOORD"), 0, DXGI_FORMAT_R32G32_FLOAT),
+        input_element(s!("COLOR"), 0, DXGI_FORMAT_R8G8B8A8_UNORM),
+    ];
+
+    let mut desc = D3D12_GRAPHICS_PIPELINE_STATE_DESC {
+        pRootSignature: windows::core::ManuallyDrop::new(Some(root_signature.clone())),
+        VS: shader_bytecode(&vs),
+        PS: shader_bytecode(&ps),
+        InputLayout: D3D12_INPUT_LAYOUT_DESC {
+            pInputElementDescs: input_elements.as_ptr(),
+            NumElements: input_elements.len() as u32,
+        },
+        PrimitiveTopologyType: D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+        NumRenderTargets: 1,
+        SampleDesc: DXGI_SAMPLE_DESC { Count: 1, Quality: 0 },
+        SampleMask: u32::MAX,
+        RasterizerState: D3D12_RASTERIZER_DESC {
+            FillMode: D3D12_FILL_MODE_SOLID,
+            CullMode: D3D12_CULL_MODE_NONE,
+            ..Default::default()
+        },
+        BlendState: alpha_blend_state(),
+        DepthStencilState: D3D12_DEPTH_STENCIL_DESC::default(),
+        ..Default::default()
+    };
+    desc.RTVFormats[0] = rtv_format;
+
+    unsafe { dev.CreateGraphicsPipelineState(&desc) }
+}
+
+fn alpha_blend_state() -> D3D12_BLEND_DESC {
+    let mut blend = D3D12_BLEND_DESC::default();
+    blend.RenderTarget[0] = D3D12_RENDER_TARGET_BLEND_DESC {
+        BlendEnable: true.into(),
+        SrcBlend: D3D12_BLEND_SRC_ALPHA,
+        DestBlend: D3D12_BLEND_INV_SRC_ALPHA,
+        BlendOp: D3D12_BLEND_OP_ADD,
+        SrcBlendAlpha: D3D12_BLEND_INV_SRC_ALPHA,
+        DestBlendAlpha: D3D12_BLEND_ZERO,
+        BlendOpAlpha: D3D12_BLEND_OP_ADD,
+        RenderTargetWriteMask: D3D12_COLOR_WRITE_ENABLE_ALL.0 as u8,
+        ..Default::default()
+    };
+    blend
+}
+
+fn input_element(
+    name: windows::core::PCSTR,
+    index: u32,
+    format: windows::Win32::Graphics::Dxgi::Common::DXGI_FORMAT,
+) -> D3D12_INPUT_ELEMENT_DESC {
+    D3D12_INPUT_ELEMENT_DESC {
+        SemanticName: name,
+        SemanticIndex: index,
+        Format: format,
+        InputSlot: 0,
+        AlignedByteOffset: D3D12_APPEND_ALIGNED_ELEMENT,
+        InputSlotClass: D3D12_INPUT_CLASSIFICATION_PER_VERTEX_DATA,
+        InstanceDataStepRate: 0,
+    }
+}
+
+fn shader_bytecode(blob: &ID3DBlob) -> D3D12_SHADER_BYTECODE {
+    unsafe {
+        D3D12_SHADER_BYTECODE {
+            pShaderBytecode: blob.GetBufferPointer(),
+            BytecodeLength: blob.GetBufferSize(),
+        }
+    }
+}
+
+fn compile_shader(
+    src: &str,
+    entry_point: windows::core::PCSTR,
+    target: windows::core::PCSTR,
+) -> windows::core::Result<ID3DBlob> {
+    use windows::Win32::Graphics::Direct3D::Fxc::D3DCompile;
+
+    let mut blob: Option<ID3DBlob> = None;
+    let mut error_blob: Option<ID3DBlob> = None;
+    unsafe {
+        D3DCompile(
+            src.as_ptr() as _,
+            src.len(),
+            None,
+            None,
+            None,
+            entry_point,
+            target,
+            0,
+            0,
+            &mut blob,
+            Some(&mut error_blob),
+        )?;
+    }
+    Ok(blob.expect("shader blob"))
+}
+
+const VERTEX_SHADER_SRC: &str = r#"
+cbuffer vertexBuffer : register(b0) {
+    float4x4 ProjectionMatrix;
+};
+struct VS_INPUT {
+    float2 pos : POSITION;
+    float2 uv  : TEXCOORD0;
+    float4 col : COLOR0;
+};
+struct PS_INPUT {
+    float4 pos : SV_POSITION;
+    float4 col : COLOR0;
+    float2 uv  : TEXCOORD0;
+};
+PS_INPUT vs_main(VS_INPUT input) {
+    PS_INPUT output;
+    output.pos = mul(ProjectionMatrix, float4(input.pos.xy, 0.f, 1.f));
+    output.col = input.col;
+    output.uv  = input.uv;
+    return output;
+}
+"#;
+
+const PIXEL_SHADER_SRC: &str = r#"
+Texture2D tex0 : register(t0);
+SamplerState sampler0 : register(s0);
+struct PS_INPUT {
+    float4 pos : SV_POSITION;
+    float4 col : COLOR0;
+    float2 uv  : TEXCOORD0;
+};
+float4 ps_main(PS_INPUT input) : SV_Target {
+    return input.col * tex0.Sample(sampler0, input.uv);
+}
+"#;
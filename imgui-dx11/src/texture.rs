@@ -0,0 +1,118 @@
+use windows::Win32::Graphics::Direct3D11::*;
+use windows::Win32::Graphics::Dxgi::Common::{DXGI_FORMAT_R8G8B8A8_UNORM, DXGI_SAMPLE_DESC};
+
+use crate::device_and_swapchain::DeviceAndSwapChain;
+
+/// D3D11 SRVs are free-standing COM objects, so textures registered via
+/// `RenderEngine::register_texture` are just extra
+/// `ID3D11ShaderResourceView`s kept in a `FxHashMap`, not slots in this
+/// struct.
+pub struct Texture {
+    tex_view: ID3D11ShaderResourceView,
+    render_target: Option<ID3D11RenderTargetView>,
+}
+
+impl Texture {
+    /// Builds and uploads imgui's font atlas as the default texture bound
+    /// when a draw command doesn't reference a registered `TextureId`.
+    pub fn new(dasc: &DeviceAndSwapChain, fonts: &mut imgui::FontAtlas) -> Result<Self, String> {
+        let font_atlas_texture = fonts.build_rgba32_texture();
+        let (_, tex_view) = upload_rgba8_texture(
+            dasc,
+            font_atlas_texture.width,
+            font_atlas_texture.height,
+            font_atlas_texture.data,
+            D3D11_BIND_SHADER_RESOURCE.0 as u32,
+        )?;
+        fonts.tex_id = imgui::TextureId::from(0usize);
+        Ok(Texture { tex_view, render_target: None })
+    }
+
+    /// Allocates an empty render target of `(width, height)`, bound with
+    /// both `D3D11_BIND_RENDER_TARGET` and `D3D11_BIND_SHADER_RESOURCE` so a
+    /// `FilterChain` pass can render into it and the next pass can sample
+    /// it as `source`.
+    pub fn new_render_target(dasc: &DeviceAndSwapChain, width: u32, height: u32) -> Result<Self, String> {
+        let (texture, tex_view) =
+            create_empty_texture(dasc, width.max(1), height.max(1), (D3D11_BIND_RENDER_TARGET.0 | D3D11_BIND_SHADER_RESOURCE.0) as u32)?;
+
+        let mut render_target = None;
+        unsafe { dasc.dev().CreateRenderTargetView(&texture, None, Some(&mut render_target)) }
+            .map_err(|e| format!("CreateRenderTargetView: {e}"))?;
+
+        Ok(Texture { tex_view, render_target })
+    }
+
+    pub fn tex_view(&self) -> ID3D11ShaderResourceView {
+        self.tex_view.clone()
+    }
+
+    /// Binds this texture's render target view as the current render
+    /// target, for a `FilterChain` pass that isn't the last in the chain.
+    pub fn set_as_render_target(&self, dasc: &DeviceAndSwapChain) {
+        let render_target =
+            self.render_target.clone().expect("set_as_render_target called on a non-render-target Texture");
+        unsafe { dasc.dev_ctx().OMSetRenderTargets(Some(&[Some(render_target)]), None) };
+    }
+}
+
+fn upload_rgba8_texture(
+    dasc: &DeviceAndSwapChain,
+    width: u32,
+    height: u32,
+    data: &[u8],
+    bind_flags: u32,
+) -> Result<(ID3D11Texture2D, ID3D11ShaderResourceView), String> {
+    let desc = D3D11_TEXTURE2D_DESC {
+        Width: width,
+        Height: height,
+        MipLevels: 1,
+        ArraySize: 1,
+        Format: DXGI_FORMAT_R8G8B8A8_UNORM,
+        SampleDesc: DXGI_SAMPLE_DESC { Count: 1, Quality: 0 },
+        Usage: D3D11_USAGE_DEFAULT,
+        BindFlags: bind_flags,
+        ..Default::default()
+    };
+    let initial_data = D3D11_SUBRESOURCE_DATA { pSysMem: data.as_ptr() as _, SysMemPitch: width * 4, ..Default::default() };
+
+    let mut texture = None;
+    unsafe { dasc.dev().CreateTexture2D(&desc, Some(&initial_data), Some(&mut texture)) }
+        .map_err(|e| format!("CreateTexture2D: {e}"))?;
+    let texture = texture.expect("texture");
+
+    let mut srv = None;
+    unsafe { dasc.dev().CreateShaderResourceView(&texture, None, Some(&mut srv)) }
+        .map_err(|e| format!("CreateShaderResourceView: {e}"))?;
+
+    Ok((texture, srv.expect("shader resource view")))
+}
+
+fn create_empty_texture(
+    dasc: &DeviceAndSwapChain,
+    width: u32,
+    height: u32,
+    bind_flags: u32,
+) -> Result<(ID3D11Texture2D, ID3D11ShaderResourceView), String> {
+    let desc = D3D11_TEXTURE2D_DESC {
+        Width: width,
+        Height: height,
+        MipLevels: 1,
+        ArraySize: 1,
+        Format: DXGI_FORMAT_R8G8B8A8_UNORM,
+        SampleDesc: DXGI_SAMPLE_DESC { Count: 1, Quality: 0 },
+        Usage: D3D11_USAGE_DEFAULT,
+        BindFlags: bind_flags,
+        ..Default::default()
+    };
+
+    let mut texture = None;
+    unsafe { dasc.dev().CreateTexture2D(&desc, None, Some(&mut texture)) }.map_err(|e| format!("CreateTexture2D: {e}"))?;
+    let texture = texture.expect("texture");
+
+    let mut srv = None;
+    unsafe { dasc.dev().CreateShaderResourceView(&texture, None, Some(&mut srv)) }
+        .map_err(|e| format!("CreateShaderResourceView: {e}"))?;
+
+    Ok((texture, srv.expect("shader resource view")))
+}
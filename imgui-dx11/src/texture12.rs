@@ -0,0 +1,117 @@
+use windows::Win32::Graphics::Direct3D12::*;
+use windows::Win32::Graphics::Dxgi::Common::{DXGI_FORMAT_R8G8B8A8_UNORM, DXGI_SAMPLE_DESC};
+
+/// Owns the CBV/SRV/UAV descriptor heap every registered texture's SRV is
+/// allocated out of. Descriptor 0 is permanently reserved for the font
+/// atlas; every texture registered afterwards (see
+/// `RenderEngine12::register_texture`) takes the next free slot.
+pub struct Texture12 {
+    heap: ID3D12DescriptorHeap,
+    descriptor_size: u32,
+    font_resource: ID3D12Resource,
+    next_free_slot: u32,
+    capacity: u32,
+}
+
+impl Texture12 {
+    pub fn new(dev: &ID3D12Device, fonts: &mut imgui::FontAtlas, capacity: u32) -> windows::core::Result<Self> {
+        let heap = create_descriptor_heap(dev, capacity)?;
+        let descriptor_size =
+            unsafe { dev.GetDescriptorHandleIncrementSize(D3D12_DESCRIPTOR_HEAP_TYPE_CBV_SRV_UAV) };
+
+        let font_atlas_texture = fonts.build_rgba32_texture();
+        let font_resource = upload_font_texture(dev, &font_atlas_texture)?;
+        fonts.tex_id = imgui::TextureId::from(0usize);
+
+        let texture = Texture12 { heap, descriptor_size, font_resource, next_free_slot: 1, capacity };
+        texture.write_srv(0, &texture.font_resource);
+        Ok(texture)
+    }
+
+    pub fn heap(&self) -> ID3D12DescriptorHeap {
+        self.heap.clone()
+    }
+
+    /// Allocates the next free slot in the descriptor heap for `resource`
+    /// and returns that slot's index, for callers that manage their own
+    /// `TextureId` bookkeeping (see `RenderEngine12::register_texture`).
+    pub fn allocate_slot(&mut self, resource: &ID3D12Resource) -> windows::core::Result<u32> {
+        if self.next_free_slot >= self.capacity {
+            return Err(windows::core::Error::from_win32());
+        }
+        let slot = self.next_free_slot;
+        self.next_free_slot += 1;
+        self.write_srv(slot, resource);
+        Ok(slot)
+    }
+
+    pub fn gpu_handle(&self, slot: u32) -> D3D12_GPU_DESCRIPTOR_HANDLE {
+        let mut handle = unsafe { self.heap.GetGPUDescriptorHandleForHeapStart() };
+        handle.ptr += (slot * self.descriptor_size) as u64;
+        handle
+    }
+
+    fn write_srv(&self, slot: u32, resource: &ID3D12Resource) {
+        let mut handle = unsafe { self.heap.GetCPUDescriptorHandleForHeapStart() };
+        handle.ptr += (slot * self.descriptor_size) as usize;
+
+        let srv_desc = D3D12_SHADER_RESOURCE_VIEW_DESC {
+            Format: DXGI_FORMAT_R8G8B8A8_UNORM,
+            ViewDimension: D3D12_SRV_DIMENSION_TEXTURE2D,
+            Shader4ComponentMapping: D3D12_DEFAULT_SHADER_4_COMPONENT_MAPPING,
+            Anonymous: D3D12_SHADER_RESOURCE_VIEW_DESC_0 {
+                Texture2D: D3D12_TEX2D_SRV { MipLevels: 1, ..Default::default() },
+            },
+        };
+
+        let dev: ID3D12Device = unsafe { resource.GetDevice() }.expect("GetDevice");
+        unsafe { dev.CreateShaderResourceView(resource, Some(&srv_desc), handle) };
+    }
+}
+
+fn create_descriptor_heap(dev: &ID3D12Device, capacity: u32) -> windows::core::Result<ID3D12DescriptorHeap> {
+    let desc = D3D12_DESCRIPTOR_HEAP_DESC {
+        Type: D3D12_DESCRIPTOR_HEAP_TYPE_CBV_SRV_UAV,
+        NumDescriptors: capacity,
+        Flags: D3D12_DESCRIPTOR_HEAP_FLAG_SHADER_VISIBLE,
+        NodeMask: 0,
+    };
+    unsafe { dev.CreateDescriptorHeap(&desc) }
+}
+
+fn upload_font_texture(dev: &ID3D12Device, font_atlas_texture: &imgui::FontAtlasTexture<'_>) -> windows::core::Result<ID3D12Resource> {
+    let heap_props = D3D12_HEAP_PROPERTIES { Type: D3D12_HEAP_TYPE_UPLOAD, ..Default::default() };
+    let desc = D3D12_RESOURCE_DESC {
+        Dimension: D3D12_RESOURCE_DIMENSION_TEXTURE2D,
+        Width: font_atlas_texture.width as u64,
+        Height: font_atlas_texture.height,
+        DepthOrArraySize: 1,
+        MipLevels: 1,
+        Format: DXGI_FORMAT_R8G8B8A8_UNORM,
+        SampleDesc: DXGI_SAMPLE_DESC { Count: 1, Quality: 0 },
+        Layout: D3D12_TEXTURE_LAYOUT_UNKNOWN,
+        ..Default::default()
+    };
+
+    let mut resource: Option<ID3D12Resource> = None;
+    unsafe {
+        dev.CreateCommittedResource(
+            &heap_props,
+            D3D12_HEAP_FLAG_NONE,
+            &desc,
+            D3D12_RESOURCE_STATE_GENERIC_READ,
+            None,
+            &mut resource,
+        )
+    }?;
+    let resource = resource.expect("font texture resource");
+
+    unsafe {
+        let mut mapped = std::ptr::null_mut();
+        resource.Map(0, None, Some(&mut mapped)).expect("Map font texture");
+        std::ptr::copy_nonoverlapping(font_atlas_texture.data.as_ptr(), mapped as *mut u8, font_atlas_texture.data.len());
+        resource.Unmap(0, None);
+    }
+
+    Ok(resource)
+}